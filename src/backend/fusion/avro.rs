@@ -0,0 +1,54 @@
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{TableProvider, TableType},
+    error::Result,
+    logical_expr::TableProviderFilterPushDown,
+    physical_plan::{projection::ProjectionExec, ExecutionPlan},
+    prelude::Expr,
+};
+
+// datafusion's avro reader reads every column when none are asked for, as for `count(*)`, and then
+// fails on the batches it didn't expect; the first column is read instead and dropped
+pub struct AvroTable(pub Arc<dyn TableProvider>);
+
+#[async_trait]
+impl TableProvider for AvroTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.0.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.0.table_type()
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match projection {
+            Some(projection) if projection.is_empty() => {
+                let plan = self.0.scan(state, Some(&vec![0]), filters, limit).await?;
+                Ok(Arc::new(ProjectionExec::try_new(vec![], plan)?))
+            }
+            projection => self.0.scan(state, projection, filters, limit).await,
+        }
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        self.0.supports_filters_pushdown(filters)
+    }
+}
@@ -0,0 +1,203 @@
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+use crate::{cli::ConnectOpts, workspace};
+
+// datasets are saved as the connect arguments they were given, so they are read again with the
+// same options, and schema files are read again too
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub name: String,
+    pub args: Vec<String>,
+    // the `<name>_<sheet>` tables of a workbook, to know which dataset a sheet belongs to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sheets: Vec<String>,
+    // databases and warehouses connected without a table are a schema of tables
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub schema: bool,
+}
+
+// views are created again from their sql, in the order they were created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewEntry {
+    pub name: String,
+    pub sql: String,
+}
+
+// queries are saved to be run again by name, they don't create anything
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryEntry {
+    pub name: String,
+    pub sql: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CatalogFile {
+    datasets: Vec<CatalogEntry>,
+    #[serde(default)]
+    views: Vec<ViewEntry>,
+    #[serde(default)]
+    queries: Vec<QueryEntry>,
+}
+
+#[derive(Debug, Default)]
+pub struct Catalog {
+    // none when there is no config dir, or the file couldn't be read and shouldn't be overwritten
+    path: Option<PathBuf>,
+    file: CatalogFile,
+}
+
+impl Catalog {
+    pub fn open(workspace: &str) -> anyhow::Result<Self> {
+        match workspace::catalog_file(workspace) {
+            Ok(path) => Self::load(&path),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            file: read(path)?,
+        })
+    }
+
+    // views come last, so the datasets they select from are connected first
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        let datasets = self.file.datasets.iter().map(|entry| entry.name.as_str());
+        datasets.chain(self.file.views.iter().map(|view| view.name.as_str()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CatalogEntry> {
+        self.file.datasets.iter().find(|entry| entry.name == name)
+    }
+
+    pub fn insert(&mut self, entry: CatalogEntry) -> anyhow::Result<()> {
+        self.update(|file| upsert(&mut file.datasets, &entry, |e| e.name == entry.name))?;
+        Ok(())
+    }
+
+    pub fn view(&self, name: &str) -> Option<&ViewEntry> {
+        self.file.views.iter().find(|view| view.name == name)
+    }
+
+    pub fn insert_view(&mut self, view: ViewEntry) -> anyhow::Result<()> {
+        self.update(|file| upsert(&mut file.views, &view, |v| v.name == view.name))?;
+        Ok(())
+    }
+
+    pub fn queries(&self) -> &[QueryEntry] {
+        &self.file.queries
+    }
+
+    pub fn query(&self, name: &str) -> Option<&QueryEntry> {
+        self.file.queries.iter().find(|query| query.name == name)
+    }
+
+    pub fn insert_query(&mut self, query: QueryEntry) -> anyhow::Result<()> {
+        self.update(|file| upsert(&mut file.queries, &query, |q| q.name == query.name))?;
+        Ok(())
+    }
+
+    pub fn remove_query(&mut self, name: &str) -> anyhow::Result<bool> {
+        self.update(|file| {
+            let len = file.queries.len();
+            file.queries.retain(|query| query.name != name);
+            file.queries.len() != len
+        })
+    }
+
+    // removes a dataset or a view
+    pub fn remove(&mut self, name: &str) -> anyhow::Result<bool> {
+        self.update(|file| {
+            let len = file.datasets.len() + file.views.len();
+            file.datasets.retain(|entry| entry.name != name);
+            file.views.retain(|view| view.name != name);
+            file.datasets.len() + file.views.len() != len
+        })
+    }
+
+    // another session on the same workspace may have changed the file since it was read, so the
+    // change is made to the file as it is now rather than to what this session read
+    fn update(&mut self, change: impl Fn(&mut CatalogFile) -> bool) -> anyhow::Result<bool> {
+        let Some(path) = &self.path else {
+            return Ok(change(&mut self.file));
+        };
+        let mut file = read(path)?;
+        let changed = change(&mut file);
+        if changed {
+            write(path, &file)?;
+        }
+        self.file = file;
+        Ok(changed)
+    }
+}
+
+// replaces the item matching, and tells whether anything changed
+fn upsert<T: Clone + PartialEq>(
+    items: &mut Vec<T>,
+    item: &T,
+    matches: impl Fn(&T) -> bool,
+) -> bool {
+    match items.iter_mut().find(|i| matches(i)) {
+        Some(i) if i == item => return false,
+        Some(i) => *i = item.clone(),
+        None => items.push(item.clone()),
+    }
+    true
+}
+
+fn read(path: &Path) -> anyhow::Result<CatalogFile> {
+    match fs::read(path) {
+        Ok(data) => serde_json::from_slice::<CatalogFile>(&data)
+            .map_err(|e| anyhow!("Invalid catalog {}: {}", path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(CatalogFile::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// the file is written aside and renamed over the catalog, so a failed write leaves the catalog as
+// it was; the arguments hold the passwords of database urls, so only the user may read it
+fn write(path: &Path, file: &CatalogFile) -> anyhow::Result<()> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid catalog path"))?;
+    fs::create_dir_all(dir)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let written = options.open(&tmp).and_then(|mut f| {
+        // a file left over by a crashed session is made private too
+        #[cfg(unix)]
+        f.set_permissions(fs::Permissions::from_mode(0o600))?;
+        f.write_all(&serde_json::to_vec_pretty(file)?)?;
+        f.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+impl CatalogEntry {
+    pub fn opts(&self) -> anyhow::Result<ConnectOpts> {
+        let args = std::iter::once("connect").chain(self.args.iter().map(String::as_str));
+        let mut opts =
+            ConnectOpts::try_parse_from(args).map_err(|e| anyhow!(e.render().to_string()))?;
+        opts.args = self.args.clone();
+        Ok(opts)
+    }
+}
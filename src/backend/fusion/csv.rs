@@ -0,0 +1,192 @@
+use std::{io::Cursor, sync::Arc};
+
+use arrow::{
+    csv::{reader::Format, ReaderBuilder},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use datafusion::{
+    catalog::TableProvider,
+    datasource::{
+        file_format::file_compression_type::FileCompressionType,
+        listing::{ListingOptions, ListingTableUrl},
+        MemTable,
+    },
+    error::DataFusionError,
+    execution::context::SessionState,
+    functions::expr_fn::nullif,
+    prelude::{cast, ident, lit, SessionContext},
+};
+use futures::{StreamExt, TryStreamExt};
+use object_store::{ObjectMeta, ObjectStore};
+use regex::Regex;
+
+use super::check_schema;
+use crate::cli::{FileOpts, ReadOpts};
+
+// datafusion's csv reader has no notion of a null marker, so it's replaced by null after reading;
+// columns holding the marker are inferred as strings, those typed by the schema are cast to it
+pub fn null_values(
+    ctx: &SessionContext,
+    table: Arc<dyn TableProvider>,
+    null_value: &str,
+    schema: Option<&SchemaRef>,
+) -> anyhow::Result<Arc<dyn TableProvider>> {
+    let df = ctx.read_table(table)?;
+    let exprs = df
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            let column = ident(field.name());
+            if field.data_type() != &DataType::Utf8 {
+                return column;
+            }
+            let value = nullif(column, lit(null_value));
+            match schema.and_then(|schema| schema.field_with_name(field.name()).ok()) {
+                Some(typed) if typed.data_type() != &DataType::Utf8 => {
+                    cast(value, typed.data_type().clone())
+                }
+                _ => value,
+            }
+            .alias(field.name())
+        })
+        .collect::<Vec<_>>();
+    Ok(df.select(exprs)?.into_view())
+}
+
+// each file is inferred on its own, so a column holding the marker in one file and only numbers in
+// another is read as strings instead of failing to merge
+pub async fn infer_schema(
+    state: &SessionState,
+    listing: &ListingOptions,
+    urls: &[ListingTableUrl],
+    read: &ReadOpts,
+    file_opts: &FileOpts,
+) -> anyhow::Result<Schema> {
+    let dialect = dialect(read, read.delimiter(file_opts));
+    let mut fields: Vec<Field> = vec![];
+    for url in urls {
+        let store = state.runtime_env().object_store(url)?;
+        let files = url
+            .list_all_files(state, store.as_ref(), &listing.file_extension)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        for file in files {
+            let schema = match is_quoted(read) {
+                true => {
+                    let rows = read.schema_infer_rows();
+                    Arc::new(
+                        infer_file(&store, &file, &dialect, file_opts.compression, rows).await?,
+                    )
+                }
+                false => listing.format.infer_schema(state, &store, &[file]).await?,
+            };
+            for field in schema.fields() {
+                match fields.iter_mut().find(|f| f.name() == field.name()) {
+                    Some(f) if f.data_type() != field.data_type() => {
+                        *f = Field::new(field.name(), DataType::Utf8, true)
+                    }
+                    Some(_) => {}
+                    None => fields.push(field.as_ref().clone()),
+                }
+            }
+        }
+    }
+    Ok(Schema::new(fields))
+}
+
+// datafusion infers the schema without the quote and escape characters, only reading with them
+pub fn is_quoted(read: &ReadOpts) -> bool {
+    read.quote != b'"' || read.escape.is_some()
+}
+
+// the file is read until enough rows are parsed; a chunk may end inside a quoted field, which
+// fails to parse until the next chunk comes
+async fn infer_file(
+    store: &Arc<dyn ObjectStore>,
+    file: &ObjectMeta,
+    dialect: &Format,
+    compression: FileCompressionType,
+    rows: usize,
+) -> anyhow::Result<Schema> {
+    let stream = store
+        .get(&file.location)
+        .await?
+        .into_stream()
+        .map_err(DataFusionError::from)
+        .boxed();
+    let mut stream = compression.convert_stream(stream)?;
+    let mut data = vec![];
+    loop {
+        let chunk = stream.try_next().await?;
+        let done = chunk.is_none();
+        data.extend_from_slice(&chunk.unwrap_or_default());
+        let end = match done {
+            true => data.len(),
+            false => match data.iter().rposition(|b| *b == b'\n') {
+                Some(end) => end + 1,
+                None => continue,
+            },
+        };
+        match dialect.infer_schema(&data[..end], Some(rows)) {
+            Ok((schema, read)) if done || read >= rows => return Ok(schema),
+            Err(e) if done => return Err(e.into()),
+            _ => {}
+        }
+    }
+}
+
+// the csv dialect of the options, without the null marker datafusion's reader has no notion of
+fn dialect(read: &ReadOpts, delimiter: u8) -> Format {
+    let mut format = Format::default()
+        .with_header(!read.no_header)
+        .with_delimiter(delimiter)
+        .with_quote(read.quote);
+    if let Some(escape) = read.escape {
+        format = format.with_escape(escape);
+    }
+    if let Some(comment) = read.comment {
+        format = format.with_comment(comment);
+    }
+    format
+}
+
+// the schema given for a file with a null marker, with every column read as a string
+pub fn string_schema(schema: &Schema) -> Schema {
+    Schema::new(
+        schema
+            .fields()
+            .iter()
+            .map(|field| Field::new(field.name(), DataType::Utf8, true))
+            .collect::<Vec<_>>(),
+    )
+}
+
+pub fn read_csv_bytes(data: &[u8], read: &ReadOpts) -> anyhow::Result<MemTable> {
+    let mut format = dialect(read, read.delimiter.unwrap_or(b','));
+    if let Some(null_value) = &read.null_value {
+        let null_regex = Regex::new(&format!("^{}$", regex::escape(null_value)))?;
+        format = format.with_null_regex(null_regex);
+    }
+
+    let schema = match &read.schema {
+        Some(schema) => {
+            if !read.no_header {
+                let header = format.infer_schema(Cursor::new(data), Some(0))?.0;
+                check_schema(schema, &header, true)?;
+            }
+            schema.clone()
+        }
+        None => Arc::new(
+            format
+                .infer_schema(Cursor::new(data), Some(read.schema_infer_rows()))?
+                .0,
+        ),
+    };
+    let reader = ReaderBuilder::new(schema.clone())
+        .with_format(format)
+        .build(Cursor::new(data))?;
+    let batches = reader.collect::<Result<Vec<_>, _>>()?;
+    Ok(MemTable::try_new(schema, vec![batches])?)
+}
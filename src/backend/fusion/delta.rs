@@ -0,0 +1,457 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context};
+use arrow::{
+    datatypes::{DataType, Field, Fields, Schema, TimeUnit},
+    json::ArrayWriter,
+};
+use chrono::{DateTime, Utc};
+use datafusion::{
+    common::ScalarValue,
+    datasource::listing::{ListingTableUrl, PartitionedFile},
+    execution::context::SessionState,
+    parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder,
+};
+use futures::TryStreamExt;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::snapshot::SnapshotTable;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Action {
+    add: Option<Add>,
+    remove: Option<Remove>,
+    meta_data: Option<MetaData>,
+    protocol: Option<Protocol>,
+    commit_info: Option<CommitInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Add {
+    path: String,
+    #[serde(default)]
+    partition_values: HashMap<String, Option<String>>,
+    size: usize,
+    modification_time: i64,
+    deletion_vector: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Remove {
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaData {
+    schema_string: String,
+    #[serde(default)]
+    partition_columns: Vec<String>,
+    #[serde(default)]
+    configuration: HashMap<String, Option<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Protocol {
+    min_reader_version: i32,
+    reader_features: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitInfo {
+    timestamp: Option<i64>,
+    in_commit_timestamp: Option<i64>,
+}
+
+// features a reader must understand; column mapping and deletion vectors fail later, and only
+// when the table uses them
+const READER_FEATURES: [&str; 4] = [
+    "columnMapping",
+    "deletionVectors",
+    "timestampNtz",
+    "vacuumProtocolCheck",
+];
+
+// the log files of one version: a commit, a checkpoint (possibly split into parts) or both
+#[derive(Debug, Default)]
+struct LogVersion {
+    commit: Option<ObjectMeta>,
+    checkpoint: Vec<(usize, ObjectMeta)>,
+    checkpoint_parts: usize,
+    // v2 checkpoints are named by a uuid and may point to sidecar files
+    v2_checkpoint: bool,
+}
+
+// replays the `_delta_log` of the table from the latest checkpoint up to the requested version,
+// or the latest version committed at or before the timestamp
+pub async fn open_table(
+    state: &SessionState,
+    path: &str,
+    version: Option<i64>,
+    timestamp: Option<DateTime<Utc>>,
+) -> anyhow::Result<SnapshotTable> {
+    let url = ListingTableUrl::parse(path)?;
+    let store = state.runtime_env().object_store(&url)?;
+    let log = url.prefix().child("_delta_log");
+
+    let versions = list_log(store.as_ref(), &log).await?;
+    let commits = versions
+        .iter()
+        .filter_map(|(version, log)| log.commit.as_ref().map(|meta| (*version, meta)))
+        .collect::<Vec<_>>();
+    let latest = *versions
+        .keys()
+        .next_back()
+        .ok_or_else(|| anyhow!("Not a delta table, no log found under: {}", path))?;
+    let target = match (version, timestamp) {
+        (Some(version), _) if versions.contains_key(&version) => version,
+        (Some(version), _) => {
+            return Err(anyhow!(
+                "Version {} not found in delta table {}, latest is {}",
+                version,
+                path,
+                latest
+            ))
+        }
+        (None, Some(timestamp)) => {
+            // commit times grow with the version, so only a few commits are read
+            let (mut low, mut high) = (0, commits.len());
+            while low < high {
+                let mid = (low + high) / 2;
+                match commit_time(store.as_ref(), commits[mid].1).await? <= timestamp {
+                    true => low = mid + 1,
+                    false => high = mid,
+                }
+            }
+            match low {
+                0 => {
+                    return Err(anyhow!(
+                        "No version of delta table {} at {}",
+                        path,
+                        timestamp
+                    ))
+                }
+                _ => commits[low - 1].0,
+            }
+        }
+        (None, None) => latest,
+    };
+
+    if versions.range(..=target).any(|(_, log)| log.v2_checkpoint) {
+        return Err(anyhow!("Delta v2 checkpoints are not supported: {}", path));
+    }
+    let checkpoint = versions
+        .range(..=target)
+        .rev()
+        .find(|(_, log)| log.checkpoint_parts > 0 && log.checkpoint.len() == log.checkpoint_parts);
+    let mut actions = vec![];
+    let start = match checkpoint {
+        Some((version, log)) => {
+            let mut parts = log.checkpoint.clone();
+            parts.sort_by_key(|(part, _)| *part);
+            for (_, meta) in parts {
+                actions.extend(read_checkpoint(store.as_ref(), &meta.location).await?);
+            }
+            version + 1
+        }
+        None => 0,
+    };
+    for version in start..=target {
+        let commit = versions
+            .get(&version)
+            .and_then(|log| log.commit.as_ref())
+            .ok_or_else(|| anyhow!("Missing commit {} in delta log of {}", version, path))?;
+        actions.extend(read_commit(store.as_ref(), &commit.location).await?);
+    }
+
+    let mut files = BTreeMap::new();
+    let mut meta_data = None;
+    for action in actions {
+        if let Some(protocol) = action.protocol {
+            if protocol.min_reader_version > 3 {
+                return Err(anyhow!(
+                    "Unsupported delta reader version: {}",
+                    protocol.min_reader_version
+                ));
+            }
+            let unsupported = protocol
+                .reader_features
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|feature| !READER_FEATURES.contains(&feature.as_str()))
+                .collect::<Vec<_>>();
+            if !unsupported.is_empty() {
+                return Err(anyhow!(
+                    "Unsupported delta reader features: {}",
+                    unsupported.join(", ")
+                ));
+            }
+        }
+        if let Some(meta) = action.meta_data {
+            meta_data = Some(meta);
+        }
+        // paths are compared once decoded, writers may escape the same path differently
+        if let Some(remove) = action.remove {
+            files.remove(&file_location(&url, &remove.path)?);
+        }
+        if let Some(add) = action.add {
+            files.insert(file_location(&url, &add.path)?, add);
+        }
+    }
+
+    let meta_data =
+        meta_data.ok_or_else(|| anyhow!("Missing metadata in delta log of {}", path))?;
+    if let Some(Some(mode)) = meta_data.configuration.get("delta.columnMapping.mode") {
+        if mode != "none" {
+            return Err(anyhow!("Delta column mapping is not supported: {}", mode));
+        }
+    }
+    let schema = parse_schema(&meta_data.schema_string)?;
+    let (partition_cols, file_fields): (Vec<_>, Vec<_>) = schema
+        .fields()
+        .iter()
+        .map(|f| f.as_ref().clone())
+        .partition(|f| meta_data.partition_columns.contains(f.name()));
+    // keep the partition columns in the order the table declares them
+    let partition_cols = meta_data
+        .partition_columns
+        .iter()
+        .map(|name| {
+            partition_cols
+                .iter()
+                .find(|f| f.name() == name)
+                .cloned()
+                .ok_or_else(|| anyhow!("Partition column not in schema: {}", name))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let files = files
+        .into_iter()
+        .map(|(location, add)| {
+            if add.deletion_vector.is_some() {
+                return Err(anyhow!(
+                    "Delta deletion vectors are not supported: {}",
+                    add.path
+                ));
+            }
+            let mut file = PartitionedFile::from(ObjectMeta {
+                location,
+                last_modified: DateTime::from_timestamp_millis(add.modification_time)
+                    .unwrap_or_default(),
+                size: add.size,
+                e_tag: None,
+                version: None,
+            });
+            file.partition_values = partition_cols
+                .iter()
+                .map(|field| match add.partition_values.get(field.name()) {
+                    Some(Some(value)) => {
+                        ScalarValue::try_from_string(value.clone(), field.data_type())
+                    }
+                    _ => ScalarValue::try_from(field.data_type()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(file)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(SnapshotTable::new(
+        url.object_store(),
+        Arc::new(Schema::new(file_fields)),
+        partition_cols,
+        files,
+    ))
+}
+
+// add and remove paths are url encoded, relative to the table root or absolute
+fn file_location(url: &ListingTableUrl, path: &str) -> anyhow::Result<Path> {
+    if path.contains("://") {
+        let file = ListingTableUrl::parse(path)?;
+        if file.object_store() != url.object_store() {
+            return Err(anyhow!("Delta file outside the table's store: {}", path));
+        }
+        return Ok(file.prefix().clone());
+    }
+    Ok(url
+        .prefix()
+        .parts()
+        .chain(Path::from_url_path(path)?.parts())
+        .collect())
+}
+
+async fn read_commit(store: &dyn ObjectStore, location: &Path) -> anyhow::Result<Vec<Action>> {
+    let bytes = store.get(location).await?.bytes().await?;
+    std::str::from_utf8(&bytes)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Invalid delta commit: {}", location))
+        })
+        .collect()
+}
+
+// the time in the commit itself, files may have been copied since; in-commit timestamps win
+// when the table enables them
+async fn commit_time(store: &dyn ObjectStore, meta: &ObjectMeta) -> anyhow::Result<DateTime<Utc>> {
+    let time = read_commit(store, &meta.location)
+        .await?
+        .into_iter()
+        .find_map(|action| action.commit_info)
+        .and_then(|info| info.in_commit_timestamp.or(info.timestamp))
+        .and_then(DateTime::from_timestamp_millis);
+    Ok(time.unwrap_or(meta.last_modified))
+}
+
+// log files are named after the zero padded version, e.g. `00000000000000000010.json`,
+// `00000000000000000010.checkpoint.parquet`, `00000000000000000010.checkpoint.0000000001.0000000002.parquet`
+// or, for v2 checkpoints, `00000000000000000010.checkpoint.<uuid>.json`
+async fn list_log(
+    store: &dyn ObjectStore,
+    log: &Path,
+) -> anyhow::Result<BTreeMap<i64, LogVersion>> {
+    let metas = store.list(Some(log)).try_collect::<Vec<_>>().await?;
+    let mut versions = BTreeMap::<i64, LogVersion>::new();
+    for meta in metas {
+        let Some(name) = meta.location.filename() else {
+            continue;
+        };
+        let parts = name.split('.').collect::<Vec<_>>();
+        let Ok(version) = parts[0].parse::<i64>() else {
+            continue;
+        };
+        match parts[1..] {
+            ["json"] => versions.entry(version).or_default().commit = Some(meta),
+            ["checkpoint", "parquet"] => {
+                let log = versions.entry(version).or_default();
+                log.checkpoint = vec![(1, meta)];
+                log.checkpoint_parts = 1;
+            }
+            ["checkpoint", _, "json" | "parquet"] => {
+                versions.entry(version).or_default().v2_checkpoint = true
+            }
+            ["checkpoint", part, total, "parquet"] => {
+                if let (Ok(part), Ok(total)) = (part.parse(), total.parse()) {
+                    let log = versions.entry(version).or_default();
+                    log.checkpoint.push((part, meta));
+                    log.checkpoint_parts = total;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(versions)
+}
+
+// checkpoints hold the same actions as the json commits, one per row, so they are converted
+// back to json and parsed the same way
+async fn read_checkpoint(store: &dyn ObjectStore, location: &Path) -> anyhow::Result<Vec<Action>> {
+    let bytes = store.get(location).await?.bytes().await?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(bytes)?.build()?;
+    let mut writer = ArrayWriter::new(vec![]);
+    for batch in reader {
+        writer.write(&batch?)?;
+    }
+    writer.finish()?;
+    let json = writer.into_inner();
+    if json.is_empty() {
+        return Ok(vec![]);
+    }
+    serde_json::from_slice(&json).with_context(|| format!("Invalid delta checkpoint: {}", location))
+}
+
+fn parse_schema(schema_string: &str) -> anyhow::Result<Schema> {
+    let schema: Value = serde_json::from_str(schema_string)?;
+    match parse_type(&schema)? {
+        DataType::Struct(fields) => Ok(Schema::new(fields)),
+        _ => Err(anyhow!("Invalid delta schema: {}", schema_string)),
+    }
+}
+
+fn parse_type(value: &Value) -> anyhow::Result<DataType> {
+    let data_type = match value {
+        Value::String(name) => match name.as_str() {
+            "string" => DataType::Utf8,
+            "long" => DataType::Int64,
+            "integer" => DataType::Int32,
+            "short" => DataType::Int16,
+            "byte" => DataType::Int8,
+            "float" => DataType::Float32,
+            "double" => DataType::Float64,
+            "boolean" => DataType::Boolean,
+            "binary" => DataType::Binary,
+            "date" => DataType::Date32,
+            "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            "timestamp_ntz" => DataType::Timestamp(TimeUnit::Microsecond, None),
+            name => match name
+                .strip_prefix("decimal(")
+                .and_then(|s| s.strip_suffix(')'))
+                .and_then(|s| s.split_once(','))
+            {
+                Some((precision, scale)) => {
+                    DataType::Decimal128(precision.trim().parse()?, scale.trim().parse()?)
+                }
+                None => return Err(anyhow!("Unsupported delta type: {}", name)),
+            },
+        },
+        Value::Object(object) => match object.get("type").and_then(Value::as_str) {
+            Some("struct") => {
+                let fields = object
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| anyhow!("Invalid delta struct: {}", value))?
+                    .iter()
+                    .map(|field| {
+                        let name = field
+                            .get("name")
+                            .and_then(Value::as_str)
+                            .ok_or_else(|| anyhow!("Invalid delta field: {}", field))?;
+                        let nullable = field
+                            .get("nullable")
+                            .and_then(Value::as_bool)
+                            .unwrap_or(true);
+                        let data_type = parse_type(field.get("type").unwrap_or(&Value::Null))?;
+                        Ok(Field::new(name, data_type, nullable))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                DataType::Struct(Fields::from(fields))
+            }
+            Some("array") => {
+                let element = parse_type(object.get("elementType").unwrap_or(&Value::Null))?;
+                let nullable = object
+                    .get("containsNull")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true);
+                DataType::List(Arc::new(Field::new("element", element, nullable)))
+            }
+            Some("map") => {
+                let key = parse_type(object.get("keyType").unwrap_or(&Value::Null))?;
+                let value_type = parse_type(object.get("valueType").unwrap_or(&Value::Null))?;
+                let nullable = object
+                    .get("valueContainsNull")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true);
+                let entries = Fields::from(vec![
+                    Field::new("key", key, false),
+                    Field::new("value", value_type, nullable),
+                ]);
+                DataType::Map(
+                    Arc::new(Field::new("key_value", DataType::Struct(entries), false)),
+                    false,
+                )
+            }
+            _ => return Err(anyhow!("Unsupported delta type: {}", value)),
+        },
+        _ => return Err(anyhow!("Unsupported delta type: {}", value)),
+    };
+    Ok(data_type)
+}
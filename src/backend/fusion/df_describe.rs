@@ -50,7 +50,7 @@ impl DescribeDataFrame {
     async fn to_record_batch(&self) -> anyhow::Result<RecordBatch> {
         let original_schema_fields = self.df.schema().fields().iter();
 
-        let batches = vec![
+        let batches = [
             self.count(),
             self.null_count(),
             self.mean(),
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray,
+        TimestampMillisecondArray,
+    },
+    datatypes::{DataType, Field, Schema, TimeUnit},
+};
+use calamine::{open_workbook_auto, Data, Range, Reader};
+use chrono::NaiveDateTime;
+use datafusion::datasource::MemTable;
+
+pub fn read_sheet(path: &str, sheet: &str) -> anyhow::Result<MemTable> {
+    let mut workbook = open_workbook_auto(path)?;
+    let range = workbook.worksheet_range(sheet)?;
+    range_to_table(&range)
+}
+
+pub fn read_sheets(path: &str) -> anyhow::Result<Vec<(String, MemTable)>> {
+    let mut workbook = open_workbook_auto(path)?;
+    workbook
+        .worksheets()
+        .into_iter()
+        .map(|(sheet, range)| Ok((sheet, range_to_table(&range)?)))
+        .collect()
+}
+
+// the first row is the header, the column types are inferred from the rest; an empty sheet is a
+// table without columns
+fn range_to_table(range: &Range<Data>) -> anyhow::Result<MemTable> {
+    let mut rows = range.rows();
+    let Some(header) = rows.next() else {
+        return Ok(MemTable::try_new(Arc::new(Schema::empty()), vec![vec![]])?);
+    };
+    let rows = rows.collect::<Vec<_>>();
+
+    let mut fields = Vec::with_capacity(header.len());
+    let mut columns = Vec::with_capacity(header.len());
+    for (idx, cell) in header.iter().enumerate() {
+        let cells = rows
+            .iter()
+            .map(|row| row.get(idx).unwrap_or(&Data::Empty))
+            .collect::<Vec<_>>();
+        let name = match cell {
+            Data::Empty => format!("column_{}", idx + 1),
+            cell => cell.to_string(),
+        };
+        let name = if fields.iter().any(|f: &Field| f.name() == &name) {
+            format!("{}_{}", name, idx + 1)
+        } else {
+            name
+        };
+        let data_type = infer_type(&cells);
+        columns.push(cells_to_array(&cells, &data_type));
+        fields.push(Field::new(name, data_type, true));
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+    Ok(MemTable::try_new(schema, vec![vec![batch]])?)
+}
+
+fn infer_type(cells: &[&Data]) -> DataType {
+    let timestamp = DataType::Timestamp(TimeUnit::Millisecond, None);
+    let mut inferred: Option<DataType> = None;
+    for cell in cells {
+        let data_type = match cell {
+            Data::Empty | Data::Error(_) => continue,
+            Data::Int(_) => DataType::Int64,
+            // excel stores every number as a float
+            Data::Float(v) if v.fract() == 0.0 => DataType::Int64,
+            Data::Float(_) => DataType::Float64,
+            Data::Bool(_) => DataType::Boolean,
+            Data::DateTime(_) | Data::DateTimeIso(_) => timestamp.clone(),
+            Data::String(_) | Data::DurationIso(_) => return DataType::Utf8,
+        };
+        inferred = match inferred {
+            None => Some(data_type),
+            Some(prev) if prev == data_type => Some(prev),
+            Some(DataType::Int64) if data_type == DataType::Float64 => Some(DataType::Float64),
+            Some(DataType::Float64) if data_type == DataType::Int64 => Some(DataType::Float64),
+            Some(_) => return DataType::Utf8,
+        };
+    }
+    inferred.unwrap_or(DataType::Utf8)
+}
+
+fn cells_to_array(cells: &[&Data], data_type: &DataType) -> ArrayRef {
+    let cells = cells.iter();
+    match data_type {
+        DataType::Int64 => Arc::new(Int64Array::from_iter(cells.map(|v| match v {
+            Data::Int(v) => Some(*v),
+            Data::Float(v) => Some(*v as i64),
+            _ => None,
+        }))),
+        DataType::Float64 => Arc::new(Float64Array::from_iter(cells.map(|v| match v {
+            Data::Int(v) => Some(*v as f64),
+            Data::Float(v) => Some(*v),
+            _ => None,
+        }))),
+        DataType::Boolean => Arc::new(BooleanArray::from_iter(cells.map(|v| match v {
+            Data::Bool(v) => Some(*v),
+            _ => None,
+        }))),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            Arc::new(TimestampMillisecondArray::from_iter(cells.map(|v| {
+                let datetime = match v {
+                    Data::DateTime(v) => v.as_datetime(),
+                    Data::DateTimeIso(v) => v.parse::<NaiveDateTime>().ok(),
+                    _ => None,
+                };
+                datetime.map(|v| v.and_utc().timestamp_millis())
+            })))
+        }
+        _ => Arc::new(StringArray::from_iter(cells.map(|v| match v {
+            Data::Empty => None,
+            v => Some(v.to_string()),
+        }))),
+    }
+}
@@ -0,0 +1,826 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context};
+use apache_avro::{types::Value as AvroValue, Reader};
+use arrow::{
+    array::{new_null_array, RecordBatch, RecordBatchOptions},
+    compute::{can_cast_types, cast},
+    datatypes::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit},
+};
+use chrono::{DateTime, Utc};
+use datafusion::{
+    common::{plan_err, stats::Precision, ColumnStatistics, ScalarValue, Statistics},
+    datasource::{
+        listing::{ListingTableUrl, PartitionedFile},
+        schema_adapter::{SchemaAdapter, SchemaAdapterFactory, SchemaMapper},
+    },
+    execution::context::SessionState,
+    parquet::arrow::PARQUET_FIELD_ID_META_KEY,
+};
+use futures::TryStreamExt;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+use serde::Deserialize;
+use serde_json::Value;
+use url::Url;
+
+use super::snapshot::SnapshotTable;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TableMetadata {
+    current_schema_id: Option<i32>,
+    // format v1 tables only have a single schema and partition spec
+    schema: Option<IcebergSchema>,
+    #[serde(default)]
+    schemas: Vec<IcebergSchema>,
+    partition_spec: Option<Vec<PartitionField>>,
+    #[serde(default)]
+    partition_specs: Vec<PartitionSpec>,
+    current_snapshot_id: Option<i64>,
+    #[serde(default)]
+    snapshots: Vec<Snapshot>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct IcebergSchema {
+    #[serde(default)]
+    schema_id: i32,
+    fields: Vec<NestedField>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NestedField {
+    id: i32,
+    name: String,
+    required: bool,
+    #[serde(rename = "type")]
+    field_type: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PartitionSpec {
+    spec_id: i32,
+    fields: Vec<PartitionField>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PartitionField {
+    name: String,
+    transform: String,
+    source_id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Snapshot {
+    snapshot_id: i64,
+    timestamp_ms: i64,
+    schema_id: Option<i32>,
+    manifest_list: Option<String>,
+    #[serde(default)]
+    manifests: Vec<String>,
+}
+
+// a live data file listed in a manifest, with its column bounds and null counts by field id
+#[derive(Debug)]
+struct DataFile {
+    spec_id: Option<i32>,
+    path: String,
+    size: usize,
+    partition: Vec<(String, AvroValue)>,
+    record_count: Option<i64>,
+    lower_bounds: HashMap<i32, Vec<u8>>,
+    upper_bounds: HashMap<i32, Vec<u8>>,
+    null_counts: HashMap<i32, i64>,
+}
+
+// reads the snapshot of the table selected by id, or the latest one taken at or before the
+// timestamp, from its manifest list and manifests
+pub async fn open_table(
+    state: &SessionState,
+    path: &str,
+    snapshot_id: Option<i64>,
+    timestamp: Option<DateTime<Utc>>,
+) -> anyhow::Result<SnapshotTable> {
+    let url = ListingTableUrl::parse(path)?;
+    let store = state.runtime_env().object_store(&url)?;
+    let location = if path.ends_with(".metadata.json") {
+        url.prefix().clone()
+    } else {
+        find_metadata(store.as_ref(), url.prefix())
+            .await?
+            .ok_or_else(|| anyhow!("Not an iceberg table, no metadata found under: {}", path))?
+    };
+    let bytes = store.get(&location).await?.bytes().await?;
+    let metadata: TableMetadata = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Invalid iceberg metadata: {}", location))?;
+
+    let snapshot = match (snapshot_id, timestamp) {
+        (Some(id), _) => Some(
+            metadata
+                .snapshots
+                .iter()
+                .find(|snapshot| snapshot.snapshot_id == id)
+                .ok_or_else(|| anyhow!("Snapshot {} not found in iceberg table {}", id, path))?,
+        ),
+        (None, Some(timestamp)) => Some(
+            metadata
+                .snapshots
+                .iter()
+                .filter(|snapshot| snapshot.timestamp_ms <= timestamp.timestamp_millis())
+                .max_by_key(|snapshot| snapshot.timestamp_ms)
+                .ok_or_else(|| anyhow!("No snapshot of iceberg table {} at {}", path, timestamp))?,
+        ),
+        // v1 tables mark a table without snapshots with -1
+        (None, None) => match metadata.current_snapshot_id.filter(|id| *id >= 0) {
+            Some(id) => Some(
+                metadata
+                    .snapshots
+                    .iter()
+                    .find(|snapshot| snapshot.snapshot_id == id)
+                    .ok_or_else(|| anyhow!("Missing current snapshot {} of {}", id, path))?,
+            ),
+            None => None,
+        },
+    };
+
+    // time travel reads with the schema the snapshot was written with
+    let schema_id = snapshot
+        .and_then(|snapshot| snapshot.schema_id)
+        .or(metadata.current_schema_id);
+    let schema = metadata
+        .schemas
+        .iter()
+        .find(|schema| Some(schema.schema_id) == schema_id)
+        .or(metadata.schema.as_ref())
+        .ok_or_else(|| anyhow!("Missing schema in iceberg metadata: {}", location))?;
+    let schema = parse_schema(schema)?;
+    let renamed = is_renamed(metadata.schemas.iter().chain(&metadata.schema));
+
+    let mut files = vec![];
+    if let Some(snapshot) = snapshot {
+        let manifests = match &snapshot.manifest_list {
+            Some(list) => read_manifest_list(store.as_ref(), &url, list).await?,
+            None => snapshot.manifests.clone(),
+        };
+        for manifest in manifests {
+            files.extend(read_manifest(store.as_ref(), &url, &manifest).await?);
+        }
+    }
+
+    // identity partitions hold the column values themselves, so they can be read from the
+    // manifests and used to prune files, as long as every file shares the same spec
+    let spec_ids = files
+        .iter()
+        .map(|file| file.spec_id)
+        .collect::<HashSet<_>>();
+    let identity_fields = match spec_ids.into_iter().collect::<Vec<_>>()[..] {
+        [Some(spec_id)] => metadata
+            .partition_specs
+            .iter()
+            .find(|spec| spec.spec_id == spec_id)
+            .map(|spec| spec.fields.as_slice())
+            .or(metadata.partition_spec.as_deref().filter(|_| spec_id == 0))
+            .unwrap_or_default()
+            .iter()
+            .filter(|field| field.transform == "identity")
+            .filter_map(|field| {
+                let column = schema.fields.get(&field.source_id)?;
+                is_partition_type(column.data_type()).then(|| (field.name.clone(), column))
+            })
+            .collect::<Vec<_>>(),
+        _ => vec![],
+    };
+    let (partition_cols, file_fields): (Vec<_>, Vec<_>) = schema
+        .schema
+        .fields()
+        .iter()
+        .map(|f| f.as_ref().clone())
+        .partition(|f| {
+            identity_fields
+                .iter()
+                .any(|(_, column)| column.name() == f.name())
+        });
+
+    let file_ids = file_fields
+        .iter()
+        .filter_map(|f| Some((f.name().clone(), schema.field_id(f.name())?)))
+        .collect::<HashMap<_, _>>();
+    let files = files
+        .into_iter()
+        .map(|file| {
+            let mut partitioned = PartitionedFile::from(ObjectMeta {
+                location: object_path(&url, &file.path)?,
+                last_modified: DateTime::default(),
+                size: file.size,
+                e_tag: None,
+                version: None,
+            });
+            partitioned.partition_values = partition_cols
+                .iter()
+                .map(|column| {
+                    let value = identity_fields
+                        .iter()
+                        .find(|(_, field)| field.name() == column.name())
+                        .and_then(|(name, _)| file.partition.iter().find(|(key, _)| key == name))
+                        .map(|(_, value)| value)
+                        .unwrap_or(&AvroValue::Null);
+                    partition_value(value, column.data_type())
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            partitioned.statistics = Some(file_statistics(&file, &file_fields, &file_ids));
+            Ok(partitioned)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let table = SnapshotTable::new(
+        url.object_store(),
+        Arc::new(Schema::new(file_fields)),
+        partition_cols,
+        files,
+    );
+    Ok(table.with_schema_adapter(Arc::new(FieldIdAdapterFactory { ids: file_ids }), !renamed))
+}
+
+pub async fn is_table(state: &SessionState, path: &str) -> anyhow::Result<bool> {
+    if path.ends_with(".metadata.json") {
+        return Ok(true);
+    }
+    let url = ListingTableUrl::parse(path)?;
+    let store = state.runtime_env().object_store(&url)?;
+    Ok(find_metadata(store.as_ref(), url.prefix()).await?.is_some())
+}
+
+// a filesystem catalog keeps each table under `<warehouse>/<namespace>/<table>/metadata`, the
+// tables are returned as `namespace.table`; the warehouse is listed a level at a time, without
+// going into the tables themselves
+pub async fn list_tables(state: &SessionState, warehouse: &str) -> anyhow::Result<Vec<String>> {
+    let url = ListingTableUrl::parse(warehouse)?;
+    let store = state.runtime_env().object_store(&url)?;
+    let mut tables = BTreeSet::new();
+    let mut dirs = vec![(url.prefix().clone(), vec![])];
+    while let Some((dir, names)) = dirs.pop() {
+        let list = store.list_with_delimiter(Some(&dir)).await?;
+        if !names.is_empty()
+            && list
+                .common_prefixes
+                .iter()
+                .any(|prefix| prefix.filename() == Some("metadata"))
+        {
+            tables.insert(names.join("."));
+            continue;
+        }
+        for prefix in list.common_prefixes {
+            let Some(name) = prefix.filename().map(str::to_string) else {
+                continue;
+            };
+            let mut names = names.clone();
+            names.push(name);
+            dirs.push((prefix, names));
+        }
+    }
+    Ok(tables.into_iter().collect())
+}
+
+pub fn table_path(warehouse: &str, table: &str) -> String {
+    format!(
+        "{}/{}",
+        warehouse.trim_end_matches('/'),
+        table.replace('.', "/")
+    )
+}
+
+// the hadoop catalog writes the latest version to `version-hint.text`, otherwise the metadata
+// file with the highest version wins, named `v3.metadata.json` or `00003-<uuid>.metadata.json`
+async fn find_metadata(store: &dyn ObjectStore, root: &Path) -> anyhow::Result<Option<Path>> {
+    let dir = root.child("metadata");
+    let metas = store.list(Some(&dir)).try_collect::<Vec<_>>().await?;
+    if let Some(hint) = metas
+        .iter()
+        .find(|meta| meta.location.filename() == Some("version-hint.text"))
+    {
+        let bytes = store.get(&hint.location).await?.bytes().await?;
+        let location = dir.child(format!(
+            "v{}.metadata.json",
+            std::str::from_utf8(&bytes)?.trim()
+        ));
+        if metas.iter().any(|meta| meta.location == location) {
+            return Ok(Some(location));
+        }
+    }
+    Ok(metas
+        .into_iter()
+        .filter_map(|meta| {
+            let name = meta.location.filename()?.strip_suffix(".metadata.json")?;
+            let version = name.strip_prefix('v').unwrap_or(name).split('-').next()?;
+            Some((version.parse::<i64>().ok()?, meta.location))
+        })
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, location)| location))
+}
+
+async fn read_avro(
+    store: &dyn ObjectStore,
+    url: &ListingTableUrl,
+    location: &str,
+) -> anyhow::Result<Vec<AvroValue>> {
+    let bytes = store
+        .get(&object_path(url, location)?)
+        .await?
+        .bytes()
+        .await?;
+    let reader = Reader::new(&bytes[..])?;
+    Ok(reader.collect::<Result<Vec<_>, _>>()?)
+}
+
+async fn read_manifest_list(
+    store: &dyn ObjectStore,
+    url: &ListingTableUrl,
+    location: &str,
+) -> anyhow::Result<Vec<String>> {
+    read_avro(store, url, location)
+        .await?
+        .iter()
+        .map(|manifest| {
+            avro_string(manifest, "manifest_path")
+                .ok_or_else(|| anyhow!("Invalid iceberg manifest list: {}", location))
+        })
+        .collect()
+}
+
+// entries deleted in the snapshot are skipped, and delete files can't be applied so they are
+// rejected
+async fn read_manifest(
+    store: &dyn ObjectStore,
+    url: &ListingTableUrl,
+    location: &str,
+) -> anyhow::Result<Vec<DataFile>> {
+    let bytes = store
+        .get(&object_path(url, location)?)
+        .await?
+        .bytes()
+        .await?;
+    let reader = Reader::new(&bytes[..])?;
+    let spec_id = reader
+        .user_metadata()
+        .get("partition-spec-id")
+        .and_then(|id| std::str::from_utf8(id).ok()?.parse().ok());
+
+    let mut files = vec![];
+    for entry in reader {
+        let entry = entry?;
+        if avro_long(&entry, "status") == Some(2) {
+            continue;
+        }
+        let data_file = avro_field(&entry, "data_file")
+            .ok_or_else(|| anyhow!("Invalid iceberg manifest: {}", location))?;
+        let path = avro_string(data_file, "file_path")
+            .ok_or_else(|| anyhow!("Invalid iceberg manifest: {}", location))?;
+        if avro_long(data_file, "content").unwrap_or(0) != 0 {
+            return Err(anyhow!("Iceberg delete files are not supported: {}", path));
+        }
+        let format = avro_string(data_file, "file_format").unwrap_or_default();
+        if !format.eq_ignore_ascii_case("parquet") {
+            return Err(anyhow!(
+                "Unsupported iceberg data file format {}: {}",
+                format,
+                path
+            ));
+        }
+        let partition = match avro_field(data_file, "partition") {
+            Some(AvroValue::Record(fields)) => fields
+                .iter()
+                .map(|(name, value)| (name.clone(), unwrap_union(value).clone()))
+                .collect(),
+            _ => vec![],
+        };
+        files.push(DataFile {
+            spec_id,
+            size: avro_long(data_file, "file_size_in_bytes").unwrap_or_default() as usize,
+            path,
+            partition,
+            record_count: avro_long(data_file, "record_count"),
+            lower_bounds: avro_map(data_file, "lower_bounds", avro_bytes),
+            upper_bounds: avro_map(data_file, "upper_bounds", avro_bytes),
+            null_counts: avro_map(data_file, "null_value_counts", avro_value_long),
+        });
+    }
+    Ok(files)
+}
+
+fn unwrap_union(value: &AvroValue) -> &AvroValue {
+    match value {
+        AvroValue::Union(_, value) => value,
+        value => value,
+    }
+}
+
+fn avro_field<'a>(record: &'a AvroValue, name: &str) -> Option<&'a AvroValue> {
+    match unwrap_union(record) {
+        AvroValue::Record(fields) => fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| unwrap_union(value)),
+        _ => None,
+    }
+}
+
+fn avro_string(record: &AvroValue, name: &str) -> Option<String> {
+    match avro_field(record, name)? {
+        AvroValue::String(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn avro_long(record: &AvroValue, name: &str) -> Option<i64> {
+    avro_value_long(avro_field(record, name)?)
+}
+
+fn avro_value_long(value: &AvroValue) -> Option<i64> {
+    match value {
+        AvroValue::Int(value) => Some(*value as i64),
+        AvroValue::Long(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn avro_bytes(value: &AvroValue) -> Option<Vec<u8>> {
+    match value {
+        AvroValue::Bytes(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+// maps keyed by field id are written as arrays of key and value records
+fn avro_map<T>(
+    record: &AvroValue,
+    name: &str,
+    value: impl Fn(&AvroValue) -> Option<T>,
+) -> HashMap<i32, T> {
+    match avro_field(record, name) {
+        Some(AvroValue::Array(entries)) => entries
+            .iter()
+            .filter_map(|entry| {
+                let key = avro_long(entry, "key")?;
+                Some((key as i32, value(avro_field(entry, "value")?)?))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+// file locations are absolute uris such as `s3://bucket/warehouse/...` or `file:/tmp/...`, read
+// from the store the table was opened with, so they must be in it
+fn object_path(url: &ListingTableUrl, location: &str) -> anyhow::Result<Path> {
+    let Ok(file) = Url::parse(location) else {
+        return Ok(Path::from(location));
+    };
+    let table: &Url = url.as_ref();
+    if (file.scheme(), file.host_str(), file.port())
+        != (table.scheme(), table.host_str(), table.port())
+    {
+        return Err(anyhow!(
+            "Iceberg file outside the table's store: {}",
+            location
+        ));
+    }
+    Ok(Path::from_url_path(file.path())?)
+}
+
+// bounds of any column, whatever the partition transform, prune files the way partition values do
+fn file_statistics(file: &DataFile, fields: &[Field], ids: &HashMap<String, i32>) -> Statistics {
+    let column_statistics = fields
+        .iter()
+        .map(|field| {
+            let Some(id) = ids.get(field.name()) else {
+                return ColumnStatistics::new_unknown();
+            };
+            let bound = |bounds: &HashMap<i32, Vec<u8>>| {
+                bounds
+                    .get(id)
+                    .and_then(|bytes| bound_value(bytes, field.data_type()))
+                    .map_or(Precision::Absent, Precision::Inexact)
+            };
+            ColumnStatistics {
+                null_count: file
+                    .null_counts
+                    .get(id)
+                    .map_or(Precision::Absent, |count| Precision::Exact(*count as usize)),
+                min_value: bound(&file.lower_bounds),
+                max_value: bound(&file.upper_bounds),
+                distinct_count: Precision::Absent,
+            }
+        })
+        .collect();
+    Statistics {
+        num_rows: file
+            .record_count
+            .map_or(Precision::Absent, |count| Precision::Exact(count as usize)),
+        total_byte_size: Precision::Exact(file.size),
+        column_statistics,
+    }
+}
+
+// bounds are serialized as little endian numbers and utf-8 strings; a column promoted from int
+// to long or float to double still has the narrow bounds in older files
+fn bound_value(bytes: &[u8], data_type: &DataType) -> Option<ScalarValue> {
+    let int = || Some(i32::from_le_bytes(bytes.try_into().ok()?));
+    let long = || match bytes.len() {
+        4 => int().map(i64::from),
+        _ => Some(i64::from_le_bytes(bytes.try_into().ok()?)),
+    };
+    let float = || Some(f32::from_le_bytes(bytes.try_into().ok()?));
+    let value = match data_type {
+        DataType::Boolean => ScalarValue::Boolean(Some(*bytes.first()? != 0)),
+        DataType::Int32 => ScalarValue::Int32(int()),
+        DataType::Int64 => ScalarValue::Int64(long()),
+        DataType::Float32 => ScalarValue::Float32(float()),
+        DataType::Float64 => ScalarValue::Float64(match bytes.len() {
+            4 => float().map(f64::from),
+            _ => Some(f64::from_le_bytes(bytes.try_into().ok()?)),
+        }),
+        DataType::Date32 => ScalarValue::Date32(int()),
+        DataType::Timestamp(TimeUnit::Microsecond, tz) => {
+            ScalarValue::TimestampMicrosecond(long(), tz.clone())
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, tz) => {
+            ScalarValue::TimestampNanosecond(long(), tz.clone())
+        }
+        DataType::Utf8 => ScalarValue::Utf8(Some(String::from_utf8(bytes.to_vec()).ok()?)),
+        _ => return None,
+    };
+    (!value.is_null()).then_some(value)
+}
+
+// a column keeps its field id when it's renamed, so two schemas naming a column differently, or
+// giving one name to different ids, mean the names in older files can't be trusted
+fn is_renamed<'a>(schemas: impl Iterator<Item = &'a IcebergSchema>) -> bool {
+    let mut names = HashMap::new();
+    let mut ids = HashMap::new();
+    for field in schemas.flat_map(|schema| &schema.fields) {
+        if *names.entry(field.id).or_insert(&field.name) != &field.name
+            || *ids.entry(&field.name).or_insert(field.id) != field.id
+        {
+            return true;
+        }
+    }
+    false
+}
+
+// data files name their columns as they were when written, so columns are matched by the field
+// ids iceberg writers record in the parquet schema, or by name in files without them
+#[derive(Debug)]
+struct FieldIdAdapterFactory {
+    ids: HashMap<String, i32>,
+}
+
+impl SchemaAdapterFactory for FieldIdAdapterFactory {
+    fn create(&self, table_schema: SchemaRef) -> Box<dyn SchemaAdapter> {
+        Box::new(FieldIdAdapter {
+            table_schema,
+            ids: self.ids.clone(),
+        })
+    }
+}
+
+struct FieldIdAdapter {
+    table_schema: SchemaRef,
+    ids: HashMap<String, i32>,
+}
+
+impl FieldIdAdapter {
+    fn file_index(&self, field: &Field, file_schema: &Schema) -> Option<usize> {
+        let field_id = |f: &Field| f.metadata().get(PARQUET_FIELD_ID_META_KEY)?.parse().ok();
+        match file_schema.fields().iter().any(|f| field_id(f).is_some()) {
+            true => {
+                let id = self.ids.get(field.name())?;
+                file_schema
+                    .fields()
+                    .iter()
+                    .position(|f| field_id(f) == Some(*id))
+            }
+            false => file_schema.index_of(field.name()).ok(),
+        }
+    }
+}
+
+impl SchemaAdapter for FieldIdAdapter {
+    fn map_column_index(&self, index: usize, file_schema: &Schema) -> Option<usize> {
+        self.file_index(self.table_schema.field(index), file_schema)
+    }
+
+    fn map_schema(
+        &self,
+        file_schema: &Schema,
+    ) -> datafusion::error::Result<(Arc<dyn SchemaMapper>, Vec<usize>)> {
+        let mut projection = vec![];
+        let mut field_mappings = vec![];
+        for field in self.table_schema.fields() {
+            let Some(index) = self.file_index(field, file_schema) else {
+                field_mappings.push(None);
+                continue;
+            };
+            let file_field = file_schema.field(index);
+            if !can_cast_types(file_field.data_type(), field.data_type()) {
+                return plan_err!(
+                    "Cannot cast iceberg column {} of type {} to {}",
+                    file_field.name(),
+                    file_field.data_type(),
+                    field.data_type()
+                );
+            }
+            field_mappings.push(Some(projection.len()));
+            projection.push(index);
+        }
+        // the file columns are read in the order of the file schema
+        let mut sorted = projection.clone();
+        sorted.sort_unstable();
+        let field_mappings = field_mappings
+            .into_iter()
+            .map(|mapping| mapping.and_then(|i| sorted.binary_search(&projection[i]).ok()))
+            .collect();
+        let mapper = FieldIdMapper {
+            table_schema: self.table_schema.clone(),
+            field_mappings,
+        };
+        Ok((Arc::new(mapper), sorted))
+    }
+}
+
+#[derive(Debug)]
+struct FieldIdMapper {
+    table_schema: SchemaRef,
+    field_mappings: Vec<Option<usize>>,
+}
+
+impl SchemaMapper for FieldIdMapper {
+    fn map_batch(&self, batch: RecordBatch) -> datafusion::error::Result<RecordBatch> {
+        let columns = self
+            .table_schema
+            .fields()
+            .iter()
+            .zip(&self.field_mappings)
+            .map(|(field, index)| match index {
+                Some(index) => cast(batch.column(*index), field.data_type()),
+                None => Ok(new_null_array(field.data_type(), batch.num_rows())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+        Ok(RecordBatch::try_new_with_options(
+            self.table_schema.clone(),
+            columns,
+            &options,
+        )?)
+    }
+
+    // only used to evaluate filters pushed into the parquet reader, which are matched by name
+    fn map_partial_batch(&self, batch: RecordBatch) -> datafusion::error::Result<RecordBatch> {
+        Ok(batch)
+    }
+}
+
+fn is_partition_type(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Boolean
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::Utf8
+            | DataType::Date32
+            | DataType::Timestamp(TimeUnit::Microsecond, _)
+    )
+}
+
+fn partition_value(value: &AvroValue, data_type: &DataType) -> anyhow::Result<ScalarValue> {
+    let value = match value {
+        AvroValue::Null => return Ok(ScalarValue::try_from(data_type)?),
+        AvroValue::Boolean(value) => ScalarValue::from(*value),
+        AvroValue::Int(value) | AvroValue::Date(value) => ScalarValue::from(*value),
+        AvroValue::Long(value)
+        | AvroValue::TimestampMicros(value)
+        | AvroValue::LocalTimestampMicros(value) => ScalarValue::from(*value),
+        AvroValue::Float(value) => ScalarValue::from(*value),
+        AvroValue::Double(value) => ScalarValue::from(*value),
+        AvroValue::String(value) => ScalarValue::from(value.as_str()),
+        value => return Err(anyhow!("Unsupported iceberg partition value: {:?}", value)),
+    };
+    Ok(value.cast_to(data_type)?)
+}
+
+// the arrow schema, with its top level fields by iceberg field id to find partition sources
+struct TableSchema {
+    schema: Schema,
+    fields: HashMap<i32, Field>,
+}
+
+impl TableSchema {
+    fn field_id(&self, name: &str) -> Option<i32> {
+        self.fields
+            .iter()
+            .find(|(_, field)| field.name() == name)
+            .map(|(id, _)| *id)
+    }
+}
+
+fn parse_schema(schema: &IcebergSchema) -> anyhow::Result<TableSchema> {
+    let fields = parse_fields(&schema.fields)?;
+    Ok(TableSchema {
+        fields: schema
+            .fields
+            .iter()
+            .map(|field| field.id)
+            .zip(fields.iter().cloned())
+            .collect(),
+        schema: Schema::new(fields),
+    })
+}
+
+fn parse_fields(fields: &[NestedField]) -> anyhow::Result<Vec<Field>> {
+    fields
+        .iter()
+        .map(|field| {
+            let data_type = parse_type(&field.field_type)?;
+            Ok(Field::new(&field.name, data_type, !field.required))
+        })
+        .collect()
+}
+
+fn parse_type(value: &Value) -> anyhow::Result<DataType> {
+    let data_type = match value {
+        Value::String(name) => match name.as_str() {
+            "boolean" => DataType::Boolean,
+            "int" => DataType::Int32,
+            "long" => DataType::Int64,
+            "float" => DataType::Float32,
+            "double" => DataType::Float64,
+            "date" => DataType::Date32,
+            "time" => DataType::Time64(TimeUnit::Microsecond),
+            "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, None),
+            "timestamptz" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            "timestamp_ns" => DataType::Timestamp(TimeUnit::Nanosecond, None),
+            "timestamptz_ns" => DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())),
+            "string" => DataType::Utf8,
+            "uuid" => DataType::FixedSizeBinary(16),
+            "binary" => DataType::Binary,
+            name => {
+                if let Some(size) = name
+                    .strip_prefix("fixed[")
+                    .and_then(|s| s.strip_suffix(']'))
+                {
+                    DataType::FixedSizeBinary(size.trim().parse()?)
+                } else if let Some((precision, scale)) = name
+                    .strip_prefix("decimal(")
+                    .and_then(|s| s.strip_suffix(')'))
+                    .and_then(|s| s.split_once(','))
+                {
+                    DataType::Decimal128(precision.trim().parse()?, scale.trim().parse()?)
+                } else {
+                    return Err(anyhow!("Unsupported iceberg type: {}", name));
+                }
+            }
+        },
+        Value::Object(object) => match object.get("type").and_then(Value::as_str) {
+            Some("struct") => {
+                let fields: Vec<NestedField> =
+                    serde_json::from_value(object.get("fields").cloned().unwrap_or(Value::Null))
+                        .with_context(|| format!("Invalid iceberg struct: {}", value))?;
+                DataType::Struct(Fields::from(parse_fields(&fields)?))
+            }
+            Some("list") => {
+                let element = parse_type(object.get("element").unwrap_or(&Value::Null))?;
+                let required = object
+                    .get("element-required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                DataType::List(Arc::new(Field::new("element", element, !required)))
+            }
+            Some("map") => {
+                let key = parse_type(object.get("key").unwrap_or(&Value::Null))?;
+                let value_type = parse_type(object.get("value").unwrap_or(&Value::Null))?;
+                let required = object
+                    .get("value-required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                let entries = Fields::from(vec![
+                    Field::new("key", key, false),
+                    Field::new("value", value_type, !required),
+                ]);
+                DataType::Map(
+                    Arc::new(Field::new("key_value", DataType::Struct(entries), false)),
+                    false,
+                )
+            }
+            _ => return Err(anyhow!("Unsupported iceberg type: {}", value)),
+        },
+        _ => return Err(anyhow!("Unsupported iceberg type: {}", value)),
+    };
+    Ok(data_type)
+}
@@ -0,0 +1,65 @@
+use std::{fs::File, io::BufReader};
+
+use anyhow::anyhow;
+use arrow::ipc::reader::StreamReader;
+use datafusion::datasource::MemTable;
+use tokio::io::AsyncReadExt;
+
+const FILE_MAGIC: &[u8; 6] = b"ARROW1";
+// feather v1 predates the ipc formats and can't be read by arrow
+const FEATHER_V1_MAGIC: &[u8; 4] = b"FEA1";
+
+// feather v2 and `.arrow` files use the ipc file format, which starts with a magic number;
+// anything else is treated as the ipc stream format
+pub async fn is_file_format(path: &str) -> anyhow::Result<bool> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut magic = [0u8; 6];
+    let n = file.read(&mut magic).await?;
+    if magic[..n].starts_with(FEATHER_V1_MAGIC) {
+        return Err(anyhow!(
+            "Feather v1 files are not supported, convert {} to feather v2 or arrow",
+            path
+        ));
+    }
+    Ok(n == magic.len() && &magic == FILE_MAGIC)
+}
+
+pub async fn read_stream(path: &str) -> anyhow::Result<MemTable> {
+    let path = path.to_string();
+    tokio::task::spawn_blocking(move || {
+        let reader = StreamReader::try_new(BufReader::new(File::open(path)?), None)?;
+        let schema = reader.schema();
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        Ok(MemTable::try_new(schema, vec![batches])?)
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/ipc");
+
+    #[tokio::test]
+    async fn tell_file_from_stream() {
+        assert!(is_file_format(&format!("{}/sales.arrow", DATA))
+            .await
+            .unwrap());
+        assert!(is_file_format(&format!("{}/sales.feather", DATA))
+            .await
+            .unwrap());
+        assert!(!is_file_format(&format!("{}/sales_stream.arrow", DATA))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn refuse_feather_v1() {
+        let path = format!("{}/legacy.feather", DATA);
+        let err = is_file_format(&path).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Feather v1 files are not supported"));
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use arrow::json::reader::{infer_json_schema_from_iterator, ReaderBuilder};
+use bytes::Buf;
+use datafusion::{datasource::MemTable, execution::context::SessionState};
+use serde_json::{Map, Value};
+
+use super::{check_schema, store};
+use crate::cli::{FileOpts, ReadOpts};
+
+const BATCH_SIZE: usize = 8192;
+const SNIFF_LEN: usize = 64 * 1024;
+
+// a file, local or remote, is read as a whole document rather than newline delimited json when
+// it holds an array or an object spread over several lines; only its first bytes are looked at
+pub async fn is_document(state: &SessionState, file_opts: &FileOpts) -> bool {
+    let path = &file_opts.filename;
+    if !store::is_remote(path) && !Path::new(path).is_file() {
+        return false;
+    }
+    let Ok(head) = store::read_head(state, path, SNIFF_LEN).await else {
+        return false;
+    };
+    let Ok(reader) = file_opts.compression.convert_read(head.reader()) else {
+        return false;
+    };
+    let line = BufReader::new(reader)
+        .lines()
+        .map_while(Result::ok)
+        .map(|line| line.trim().to_string())
+        .find(|line| !line.is_empty());
+    line.is_some_and(|line| line.starts_with('[') || line == "{")
+}
+
+// datafusion only reads newline delimited json, so documents are parsed with serde and loaded
+// into memory
+pub async fn read_json(
+    state: &SessionState,
+    file_opts: &FileOpts,
+    read: &ReadOpts,
+) -> anyhow::Result<MemTable> {
+    let data = store::read_file(state, file_opts).await?;
+    read_json_bytes(&data, read)
+}
+
+// the input may hold several values, as newline delimited json does, each either a record or an
+// array of records
+pub fn read_json_bytes(data: &[u8], read: &ReadOpts) -> anyhow::Result<MemTable> {
+    let path = match &read.json_path {
+        Some(path) => parse_path(path)?,
+        None => vec![],
+    };
+    let mut rows = vec![];
+    for value in serde_json::Deserializer::from_slice(data).into_iter::<Value>() {
+        for value in select(value?, &path) {
+            match value {
+                Value::Array(values) => rows.extend(values),
+                value => rows.push(value),
+            }
+        }
+    }
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let row = match row {
+                Value::Object(row) => row,
+                // arrays of plain values become a single column
+                value => Map::from_iter([("value".to_string(), value)]),
+            };
+            match read.flatten {
+                true => Value::Object(flatten(row)),
+                false => Value::Object(row),
+            }
+        })
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        return match &read.json_path {
+            Some(path) => Err(anyhow!("No json records found at path: {}", path)),
+            None => Err(anyhow!("No json records found")),
+        };
+    }
+
+    let inferred =
+        infer_json_schema_from_iterator(rows.iter().take(read.schema_infer_rows()).map(Ok))?;
+    let schema = match &read.schema {
+        Some(schema) => {
+            check_schema(schema, &inferred, false)?;
+            schema.clone()
+        }
+        None => Arc::new(inferred),
+    };
+    let mut decoder = ReaderBuilder::new(schema.clone()).build_decoder()?;
+    let mut batches = vec![];
+    for chunk in rows.chunks(BATCH_SIZE) {
+        decoder.serialize(chunk)?;
+        if let Some(batch) = decoder.flush()? {
+            batches.push(batch);
+        }
+    }
+    Ok(MemTable::try_new(schema, vec![batches])?)
+}
+
+#[derive(Debug, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+// a small subset of jsonpath: `$.data.items`, `$.pages[0].rows`, `$['odd key']` or `$.pages[*].rows`
+fn parse_path(path: &str) -> anyhow::Result<Vec<PathSegment>> {
+    let invalid = || anyhow!("Invalid json path: {}", path);
+    let mut rest = path.trim().strip_prefix('$').unwrap_or(path.trim());
+    let mut segments = vec![];
+    while !rest.is_empty() {
+        if let Some(tail) = rest.strip_prefix('[') {
+            let (inner, tail) = tail.split_once(']').ok_or_else(invalid)?;
+            let inner = inner.trim();
+            let segment = if inner == "*" {
+                PathSegment::Wildcard
+            } else if let Ok(index) = inner.parse() {
+                PathSegment::Index(index)
+            } else {
+                let key = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                    .ok_or_else(invalid)?;
+                PathSegment::Key(key.to_string())
+            };
+            segments.push(segment);
+            rest = tail;
+        } else {
+            let tail = rest.strip_prefix('.').unwrap_or(rest);
+            let end = tail.find(['.', '[']).unwrap_or(tail.len());
+            match &tail[..end] {
+                "" => return Err(invalid()),
+                "*" => segments.push(PathSegment::Wildcard),
+                key => segments.push(PathSegment::Key(key.to_string())),
+            }
+            rest = &tail[end..];
+        }
+    }
+    Ok(segments)
+}
+
+fn select(value: Value, path: &[PathSegment]) -> Vec<Value> {
+    let Some((segment, rest)) = path.split_first() else {
+        return vec![value];
+    };
+    match (segment, value) {
+        (PathSegment::Key(key), Value::Object(mut object)) => match object.remove(key) {
+            Some(value) => select(value, rest),
+            None => vec![],
+        },
+        (PathSegment::Index(index), Value::Array(mut values)) if *index < values.len() => {
+            select(values.swap_remove(*index), rest)
+        }
+        (PathSegment::Wildcard, Value::Array(values)) => values
+            .into_iter()
+            .flat_map(|value| select(value, rest))
+            .collect(),
+        (PathSegment::Wildcard, Value::Object(object)) => object
+            .into_iter()
+            .flat_map(|(_, value)| select(value, rest))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// nested objects become `parent.child` columns, arrays are kept as lists
+fn flatten(object: Map<String, Value>) -> Map<String, Value> {
+    let mut flat = Map::new();
+    for (key, value) in object {
+        match value {
+            Value::Object(child) => {
+                for (child_key, value) in flatten(child) {
+                    flat.insert(format!("{}.{}", key, child_key), value);
+                }
+            }
+            value => {
+                flat.insert(key, value);
+            }
+        }
+    }
+    flat
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use datafusion::datasource::TableProvider;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct Opts {
+        #[command(flatten)]
+        read: ReadOpts,
+    }
+
+    fn read_opts(args: &[&str]) -> ReadOpts {
+        Opts::parse_from(std::iter::once("json").chain(args.iter().copied())).read
+    }
+
+    fn key(key: &str) -> PathSegment {
+        PathSegment::Key(key.to_string())
+    }
+
+    #[test]
+    fn parse_paths() {
+        assert_eq!(
+            parse_path("$.data.items").unwrap(),
+            vec![key("data"), key("items")]
+        );
+        assert_eq!(
+            parse_path("$.pages[0].rows").unwrap(),
+            vec![key("pages"), PathSegment::Index(0), key("rows")]
+        );
+        assert_eq!(
+            parse_path("$['odd key'][\"x.y\"]").unwrap(),
+            vec![key("odd key"), key("x.y")]
+        );
+        assert_eq!(
+            parse_path("$.pages[*].*").unwrap(),
+            vec![key("pages"), PathSegment::Wildcard, PathSegment::Wildcard]
+        );
+        assert_eq!(parse_path("data").unwrap(), vec![key("data")]);
+        assert!(parse_path("$").unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_paths() {
+        for path in ["$.", "$..a", "$.a.", "$[abc]", "$['a]", "$[0", "$.a[]"] {
+            let err = parse_path(path).unwrap_err();
+            assert_eq!(err.to_string(), format!("Invalid json path: {}", path));
+        }
+    }
+
+    #[test]
+    fn select_nested_and_wildcards() {
+        let value = json!({
+            "data": {"pages": [
+                {"rows": [{"id": 1}, {"id": 2}]},
+                {"rows": [{"id": 3}]},
+                {"other": true},
+            ]}
+        });
+        let path = parse_path("$.data.pages[*].rows").unwrap();
+        assert_eq!(
+            select(value.clone(), &path),
+            vec![json!([{"id": 1}, {"id": 2}]), json!([{"id": 3}])]
+        );
+        let path = parse_path("$.data.pages[0].rows[*].id").unwrap();
+        assert_eq!(select(value.clone(), &path), vec![json!(1), json!(2)]);
+        let path = parse_path("$.data.pages[5]").unwrap();
+        assert!(select(value.clone(), &path).is_empty());
+        let path = parse_path("$.data.missing").unwrap();
+        assert!(select(value, &path).is_empty());
+    }
+
+    #[test]
+    fn flatten_objects_not_arrays() {
+        let Value::Object(object) = json!({
+            "id": 1,
+            "user": {"name": "a", "address": {"city": "b"}},
+            "tags": ["x", "y"],
+            "items": [{"sku": {"code": 1}}],
+        }) else {
+            unreachable!()
+        };
+        let flat = flatten(object);
+        assert_eq!(
+            Value::Object(flat),
+            json!({
+                "id": 1,
+                "user.name": "a",
+                "user.address.city": "b",
+                "tags": ["x", "y"],
+                "items": [{"sku": {"code": 1}}],
+            })
+        );
+    }
+
+    #[test]
+    fn records_at_path() {
+        let read = read_opts(&["--json-path", "$.pages[*].rows", "--flatten"]);
+        let data = br#"{"pages": [{"rows": [{"id": 1, "a": {"b": 2}}]}, {"rows": [{"id": 3}]}]}"#;
+        let table = read_json_bytes(data, &read).unwrap();
+        let names = table
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["id", "a.b"]);
+
+        let read = read_opts(&["--json-path", "$.missing"]);
+        let err = read_json_bytes(data, &read).unwrap_err();
+        assert_eq!(err.to_string(), "No json records found at path: $.missing");
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::anyhow;
+use arrow::datatypes::DataType;
+use datafusion::{datasource::listing::ListingTableUrl, execution::context::SessionState};
+use futures::{StreamExt, TryStreamExt};
+use glob::{MatchOptions, Pattern};
+
+use super::store;
+
+// only a handful of files are inspected, the same as datafusion does for its own inference
+const INFER_FILES: usize = 10;
+
+// discovers hive style `key=value` directories below the table path and exposes them as
+// partition columns, so filters on them prune whole directories; the keys are taken from the
+// first files listed only, and every column is a string, cast it in sql to compare as a number
+pub async fn partition_cols(
+    state: &SessionState,
+    path: &str,
+    ext: &str,
+) -> anyhow::Result<Vec<(String, DataType)>> {
+    let url = ListingTableUrl::parse(path)?;
+    // the files a url glob matches are each a table path of their own, without partitions
+    if split_url_glob(path).is_some() || (!url.is_collection() && !path.contains(['*', '?', '['])) {
+        return Ok(vec![]);
+    }
+
+    let store = state.runtime_env().object_store(&url)?;
+    let files = url
+        .list_all_files(state, store.as_ref(), ext)
+        .await?
+        .take(INFER_FILES)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let prefix = url.prefix().as_ref();
+    let keys = files
+        .iter()
+        .map(|file| {
+            let relative = file
+                .location
+                .as_ref()
+                .strip_prefix(prefix)
+                .unwrap_or_default()
+                .trim_start_matches('/');
+            let mut parts = relative.split('/').collect::<Vec<_>>();
+            parts.pop();
+            parts
+                .into_iter()
+                .filter_map(|part| part.split_once('=').map(|(key, _)| key.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    match keys.split_first() {
+        None => Ok(vec![]),
+        Some((first, rest)) if rest.iter().all(|keys| keys == first) => Ok(first
+            .iter()
+            .map(|key| (key.clone(), DataType::Utf8))
+            .collect()),
+        Some(_) => Err(anyhow!(
+            "Inconsistent partition directories under: {}",
+            path
+        )),
+    }
+}
+
+// datafusion only expands globs in local paths, for a url the glob is matched here against the
+// files under the directory before its first wildcard; `*` doesn't match across `/`
+pub async fn glob_files(
+    state: &SessionState,
+    path: &str,
+    ext: &str,
+) -> anyhow::Result<Option<Vec<ListingTableUrl>>> {
+    let Some((dir, glob)) = split_url_glob(path) else {
+        return Ok(None);
+    };
+    let pattern = Pattern::new(glob).map_err(|e| anyhow!("Invalid glob {}: {}", path, e))?;
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+
+    let url = ListingTableUrl::parse(dir)?;
+    let store = state.runtime_env().object_store(&url)?;
+    let prefix = url.prefix().as_ref();
+    let files = store
+        .list(Some(url.prefix()))
+        .try_filter(|meta| {
+            let location = meta.location.as_ref();
+            let relative = location
+                .strip_prefix(prefix)
+                .unwrap_or(location)
+                .trim_start_matches('/');
+            futures::future::ready(
+                location.ends_with(ext) && pattern.matches_with(relative, options),
+            )
+        })
+        .try_collect::<Vec<_>>()
+        .await?;
+    if files.is_empty() {
+        return Err(anyhow!("No files match: {}", path));
+    }
+
+    let base = url.object_store();
+    let urls = files
+        .iter()
+        .map(|file| ListingTableUrl::parse(format!("{}{}", base.as_str(), file.location)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(urls))
+}
+
+// in a http url `?` starts the query, and http servers can't be listed anyway
+pub fn split_url_glob(path: &str) -> Option<(&str, &str)> {
+    if !store::is_remote(path) || path.starts_with("http://") || path.starts_with("https://") {
+        return None;
+    }
+    let pos = path.find(['*', '?', '['])?;
+    let dir = path[..pos].rfind('/').map_or(0, |i| i + 1);
+    Some(path.split_at(dir))
+}
@@ -1,85 +1,963 @@
+mod avro;
+mod catalog;
+mod csv;
+mod delta;
 mod describe;
 mod df_describe;
-use std::ops::Deref;
+mod excel;
+mod iceberg;
+mod ipc;
+mod json;
+mod listing;
+mod mysql;
+mod postgres;
+mod remote;
+mod snapshot;
+mod sqlite;
+mod stdin;
+mod store;
+mod text;
+mod watch;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::anyhow;
 
-use arrow::{array::RecordBatch, util::pretty::pretty_format_batches};
-use datafusion::prelude::{CsvReadOptions, NdJsonReadOptions, SessionConfig, SessionContext};
+use arrow::{
+    array::{ArrayRef, RecordBatch, StringArray},
+    datatypes::{DataType, Schema, SchemaRef},
+    util::pretty::pretty_format_batches,
+};
+use avro::AvroTable;
+use catalog::{Catalog, CatalogEntry, QueryEntry, ViewEntry};
+use datafusion::{
+    catalog::{SchemaProvider, TableProvider},
+    catalog_common::MemorySchemaProvider,
+    datasource::{
+        file_format::options::{ArrowReadOptions, ReadOptions},
+        listing::{ListingTable, ListingTableConfig, ListingTableUrl},
+    },
+    prelude::{
+        col, AvroReadOptions, CsvReadOptions, NdJsonReadOptions, ParquetReadOptions, SessionConfig,
+        SessionContext,
+    },
+    sql::{
+        parser::Statement,
+        sqlparser::ast::{ObjectType, Statement as SqlStatement},
+        TableReference,
+    },
+};
 use describe::DataFrameDescriber;
+use mysql::MySqlTable;
+use postgres::PostgresTable;
+use sqlite::SqliteTable;
+use url::Url;
+use watch::Watch;
 
 use crate::{
-    cli::{ConnectOpts, DataSetConn},
-    Backend, ReplDisplay,
+    cli::{identifier, ConnectOpts, DataSetConn, FileFormat, FileOpts},
+    workspace, Backend, ReplDisplay,
 };
 
-pub struct DataFusionBackend(SessionContext);
+enum Dataset {
+    Table(Arc<dyn TableProvider>),
+    Schema(Arc<dyn SchemaProvider>),
+    // the `<name>_<sheet>` tables of a workbook
+    Sheets(Vec<(String, Arc<dyn TableProvider>)>),
+}
+
+// views created or dropped by a statement, kept in the catalog with the datasets
+enum ViewChange {
+    Create(String),
+    Drop(Vec<String>),
+}
+
+pub struct DataFusionBackend {
+    ctx: SessionContext,
+    workspace: String,
+    // the options each dataset was connected with, to read it again on refresh
+    datasets: HashMap<String, ConnectOpts>,
+    // the tables each workbook registered, one per sheet
+    sheets: HashMap<String, Vec<String>>,
+    watches: HashMap<String, Watch>,
+    // what each object store was built with besides its url, as it serves every dataset under it
+    stores: HashMap<Url, String>,
+    catalog: Catalog,
+    // datasets and views of the catalog not connected yet in this session
+    saved: HashSet<String>,
+}
 
 impl DataFusionBackend {
     pub fn new() -> Self {
+        Self::open(workspace::DEFAULT_WORKSPACE)
+    }
+
+    pub fn open(workspace: &str) -> Self {
+        let catalog = Catalog::open(workspace).unwrap_or_else(|e| {
+            eprintln!(
+                "Failed to read the catalog of workspace {}: {}",
+                workspace, e
+            );
+            Catalog::default()
+        });
+        Self::with_catalog(workspace, catalog)
+    }
+
+    fn with_catalog(workspace: &str, catalog: Catalog) -> Self {
         let mut config = SessionConfig::new();
         config.options_mut().catalog.information_schema = true;
         let ctx = SessionContext::new_with_config(config);
-        Self(ctx)
+        let saved = catalog.names().map(|name| name.to_string()).collect();
+        Self {
+            ctx,
+            workspace: workspace.to_string(),
+            datasets: HashMap::new(),
+            sheets: HashMap::new(),
+            watches: HashMap::new(),
+            stores: HashMap::new(),
+            catalog,
+            saved,
+        }
     }
 }
 
 impl Backend for DataFusionBackend {
     async fn connect(&mut self, opts: &ConnectOpts) -> anyhow::Result<()> {
-        match &opts.conn {
+        // parsing options for lines make a file text, whatever its extension
+        let format = opts
+            .format
+            .or(opts.text.is_set().then_some(FileFormat::Text));
+        let mut conn = opts.conn.clone().detect().map_err(|e| anyhow!(e))?;
+        let sample = conn.sample_file();
+        if let Some(sample) = &sample {
+            conn = conn.with_sample_file(sample).map_err(|e| anyhow!(e))?;
+        }
+        if let Some(format) = format {
+            conn = conn.with_format(format).map_err(|e| anyhow!(e))?;
+        }
+        // datafusion lists only ipc files, streams are read one file at a time
+        if let (DataSetConn::Arrow(_), Some(sample)) = (&conn, &sample) {
+            if !ipc::is_file_format(sample).await? {
+                return Err(anyhow!(
+                    "Arrow ipc stream files can't be connected as a directory: {}",
+                    sample
+                ));
+            }
+        }
+        if let Some(path) = conn.path().filter(|path| store::is_remote(path)) {
+            store::register(&self.ctx, &mut self.stores, path, opts.endpoint.as_deref())?;
+        }
+        if opts.read.schema.is_some()
+            && !matches!(
+                conn,
+                DataSetConn::Csv(_)
+                    | DataSetConn::NdJson(_)
+                    | DataSetConn::Text(_)
+                    | DataSetConn::Stdin(_)
+            )
+        {
+            return Err(anyhow!(
+                "Schema is only supported for csv, json and text datasets"
+            ));
+        }
+        if opts.read.null_value.is_some()
+            && !matches!(
+                conn,
+                DataSetConn::Csv(_) | DataSetConn::Text(_) | DataSetConn::Stdin(_)
+            )
+        {
+            return Err(anyhow!(
+                "Null value is only supported for csv and text datasets"
+            ));
+        }
+        if (opts.read.json_path.is_some() || opts.read.flatten)
+            && !matches!(conn, DataSetConn::NdJson(_) | DataSetConn::Stdin(_))
+        {
+            return Err(anyhow!(
+                "Json path and flatten are only supported for json datasets"
+            ));
+        }
+        if opts.version.is_some() && !matches!(conn, DataSetConn::Delta(_)) {
+            return Err(anyhow!("Version is only supported for delta tables"));
+        }
+        if opts.snapshot_id.is_some() && !matches!(conn, DataSetConn::Iceberg(_)) {
+            return Err(anyhow!("Snapshot id is only supported for iceberg tables"));
+        }
+        if opts.timestamp.is_some()
+            && !matches!(conn, DataSetConn::Delta(_) | DataSetConn::Iceberg(_))
+        {
+            return Err(anyhow!(
+                "Timestamp is only supported for delta and iceberg tables"
+            ));
+        }
+
+        let watch = match (opts.watch, conn.path()) {
+            (true, Some(path)) => Some(Watch::try_new(path)?),
+            (true, None) => {
+                return Err(anyhow!(
+                    "Watch is only supported for local files and directories"
+                ))
+            }
+            (false, _) => None,
+        };
+
+        // the old dataset is put back if connecting again fails
+        let old = match opts.replace {
+            true => self.take_dataset(&opts.name)?,
+            false => None,
+        };
+        if let Err(e) = self.register_dataset(&conn, opts).await {
+            if let Some(old) = old {
+                self.restore_dataset(&opts.name, old)?;
+            }
+            return Err(e);
+        }
+
+        // stdin can't be read again, and datasets connected through the api have no arguments
+        if !opts.args.is_empty() && !matches!(conn, DataSetConn::Stdin(_)) {
+            let entry = CatalogEntry {
+                name: opts.name.clone(),
+                args: opts.args.clone(),
+                sheets: self.sheets.get(&opts.name).cloned().unwrap_or_default(),
+                schema: self.is_schema(&opts.name),
+            };
+            if let Err(e) = self.catalog.insert(entry) {
+                eprintln!("Failed to save dataset {} to the catalog: {}", opts.name, e);
+            }
+        }
+        self.saved.remove(&opts.name);
+        self.datasets.insert(opts.name.clone(), opts.clone());
+        match watch {
+            Some(watch) => self.watches.insert(opts.name.clone(), watch),
+            None => self.watches.remove(&opts.name),
+        };
+        Ok(())
+    }
+
+    async fn disconnect(&mut self, name: &str) -> anyhow::Result<()> {
+        let removed = self.take_dataset(name)?.is_some();
+        // a watched dataset may be gone after a failed reload, but still be watched
+        let known = self.datasets.remove(name).is_some();
+        self.watches.remove(name);
+        // a saved dataset not connected yet stays unconnected for the rest of the session
+        let saved = self.saved.remove(name);
+        if !removed && !known && !saved {
+            return Err(anyhow!("Dataset not found: {}", name));
+        }
+        Ok(())
+    }
+
+    // connecting again re-infers the schema of files and re-reads in memory tables; a saved
+    // dataset not connected yet is read afresh by connecting it, as using it would
+    async fn refresh(&mut self, name: &str) -> anyhow::Result<()> {
+        if !self.datasets.contains_key(name) && self.saved.contains(name) {
+            return self.connect_saved(name).await;
+        }
+        let mut opts = self
+            .datasets
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Dataset not found: {}", name))?;
+        if matches!(opts.conn, DataSetConn::Stdin(_)) {
+            return Err(anyhow!(
+                "Dataset {} was read from stdin, connect it again",
+                name
+            ));
+        }
+        opts.replace = true;
+        self.connect(&opts).await
+    }
+
+    async fn reload_changed(&mut self) -> Vec<(String, anyhow::Result<()>)> {
+        let changed = self
+            .watches
+            .iter_mut()
+            .filter_map(|(name, watch)| watch.poll().then(|| name.clone()))
+            .collect::<Vec<_>>();
+        let mut results = vec![];
+        for name in changed {
+            let result = self.refresh(&name).await;
+            results.push((name, result));
+        }
+        results
+    }
+
+    async fn forget(&mut self, name: &str) -> anyhow::Result<()> {
+        if !self.catalog.remove(name)? {
+            return Err(anyhow!("Dataset or view not in the catalog: {}", name));
+        }
+        self.saved.remove(name);
+        Ok(())
+    }
+
+    // a workspace starts with none of its datasets connected, they are connected when used
+    async fn use_workspace(&mut self, name: &str) -> anyhow::Result<()> {
+        if !workspace::exists(name) {
+            return Err(anyhow!("Workspace not found: {}, create it first", name));
+        }
+        let catalog = Catalog::open(name)?;
+        *self = Self::with_catalog(name, catalog);
+        Ok(())
+    }
+
+    fn workspace(&self) -> &str {
+        &self.workspace
+    }
+
+    // a query is only checked to parse, the datasets it reads may not be connected yet
+    async fn save_query(&mut self, name: &str, sql: &str) -> anyhow::Result<()> {
+        if self.parse_sql(sql).is_none() {
+            return Err(anyhow!("Invalid sql of query {}: {}", name, sql));
+        }
+        self.catalog.insert_query(QueryEntry {
+            name: name.to_string(),
+            sql: sql.to_string(),
+        })
+    }
+
+    fn query_sql(&self, name: &str) -> anyhow::Result<String> {
+        self.catalog
+            .query(name)
+            .map(|query| query.sql.clone())
+            .ok_or_else(|| anyhow!("Query not found: {}", name))
+    }
+
+    async fn list_queries(&self) -> anyhow::Result<impl ReplDisplay> {
+        let queries = self.catalog.queries();
+        let batch = RecordBatch::try_from_iter([
+            (
+                "name",
+                Arc::new(StringArray::from_iter_values(
+                    queries.iter().map(|q| q.name.as_str()),
+                )) as ArrayRef,
+            ),
+            (
+                "sql",
+                Arc::new(StringArray::from_iter_values(
+                    queries.iter().map(|q| q.sql.as_str()),
+                )),
+            ),
+        ])?;
+        Ok(batch)
+    }
+
+    async fn delete_query(&mut self, name: &str) -> anyhow::Result<()> {
+        if !self.catalog.remove_query(name)? {
+            return Err(anyhow!("Query not found: {}", name));
+        }
+        Ok(())
+    }
+
+    // saved datasets and views not connected yet are listed from the catalog, as connecting
+    // them could wait on every remote database
+    async fn list(&mut self) -> anyhow::Result<impl ReplDisplay> {
+        let sql = "select table_schema,table_name,table_type from information_schema.tables where table_schema != 'information_schema'";
+        let df = self.ctx.sql(sql).await?;
+
+        let default_schema = self.state().config_options().catalog.default_schema.clone();
+        let mut rows = vec![];
+        for name in self
+            .catalog
+            .names()
+            .filter(|name| self.saved.contains(*name))
+        {
+            match self.catalog.get(name) {
+                Some(entry) if entry.schema => rows.push((name, None, "SAVED SCHEMA")),
+                Some(entry) if !entry.sheets.is_empty() => {
+                    for sheet in &entry.sheets {
+                        rows.push((&default_schema, Some(sheet.as_str()), "SAVED TABLE"));
+                    }
+                }
+                Some(_) => rows.push((&default_schema, Some(name), "SAVED TABLE")),
+                None => rows.push((&default_schema, Some(name), "SAVED VIEW")),
+            }
+        }
+        let saved = RecordBatch::try_from_iter([
+            (
+                "table_schema",
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))) as ArrayRef,
+            ),
+            (
+                "table_name",
+                Arc::new(StringArray::from_iter(rows.iter().map(|r| r.1))),
+            ),
+            (
+                "table_type",
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.2))),
+            ),
+        ])?;
+        let df = df.union(self.ctx.read_batch(saved)?)?.sort(vec![
+            col("table_schema").sort(true, false),
+            col("table_name").sort(true, false),
+        ])?;
+        Ok(df)
+    }
+
+    async fn schema(&mut self, name: &str) -> anyhow::Result<impl ReplDisplay> {
+        self.connect_used(&[TableReference::from(name)]).await;
+        let df = self.ctx.sql(&format!("DESCRIBE {}", name)).await?;
+        Ok(df)
+    }
+    async fn describe(&mut self, name: &str) -> anyhow::Result<impl ReplDisplay> {
+        self.connect_used(&[TableReference::from(name)]).await;
+        let df = self.ctx.sql(&format!("select * from {}", name)).await?;
+        let ddf = DataFrameDescriber::try_new(df)?;
+        ddf.describe().await
+    }
+    async fn head(&mut self, name: &str, n: usize) -> anyhow::Result<impl ReplDisplay> {
+        self.connect_used(&[TableReference::from(name)]).await;
+        let df = self
+            .ctx
+            .sql(&format!("SELECT * FROM {} LIMIT {}", name, n))
+            .await?;
+        Ok(df)
+    }
+    async fn sql(&mut self, sql: &str) -> anyhow::Result<impl ReplDisplay> {
+        // sql that doesn't parse is left for the query to report
+        let statement = self.parse_sql(sql);
+        let change = statement.as_ref().and_then(view_change);
+        let mut tables = statement
+            .map(|statement| self.table_references(&statement))
+            .unwrap_or_default();
+        if let Some(ViewChange::Drop(names)) = &change {
+            tables.extend(names.iter().map(|name| TableReference::from(name.as_str())));
+        }
+        self.connect_used(&tables).await;
+        let df = self.ctx.sql(sql).await?;
+
+        let saved = match change {
+            Some(ViewChange::Create(name)) => self.catalog.insert_view(ViewEntry {
+                name,
+                sql: sql.to_string(),
+            }),
+            Some(ViewChange::Drop(names)) => names
+                .iter()
+                .try_for_each(|name| self.catalog.remove(name).map(|_| ())),
+            None => Ok(()),
+        };
+        if let Err(e) = saved {
+            eprintln!("Failed to save views to the catalog: {}", e);
+        }
+        Ok(df)
+    }
+}
+
+impl DataFusionBackend {
+    // like `register_csv` and the others, but a url glob is registered as the files it matches
+    async fn register_files<'a>(
+        &self,
+        name: &str,
+        path: &str,
+        options: impl ReadOptions<'a>,
+    ) -> anyhow::Result<()> {
+        let table = self.listing_table(path, options).await?;
+        self.register_table(name, table)?;
+        Ok(())
+    }
+
+    async fn listing_table<'a>(
+        &self,
+        path: &str,
+        options: impl ReadOptions<'a>,
+    ) -> anyhow::Result<Arc<dyn TableProvider>> {
+        let state = self.state();
+        let config = self.copied_config();
+        let listing = options.to_listing_options(&config, self.copied_table_options());
+        let urls = self.table_urls(path, &listing.file_extension).await?;
+        // for a url glob the schema is inferred from the first file it matches
+        let schema = options
+            .get_resolved_schema(&config, state, urls[0].clone())
+            .await?;
+        let config = ListingTableConfig::new_with_multi_paths(urls)
+            .with_listing_options(listing)
+            .with_schema(schema);
+        Ok(Arc::new(ListingTable::try_new(config)?))
+    }
+
+    // the schema inferred from the files, without the partition columns
+    async fn file_schema<'a>(
+        &self,
+        path: &str,
+        options: impl ReadOptions<'a>,
+    ) -> anyhow::Result<SchemaRef> {
+        let config = self.copied_config();
+        let listing = options.to_listing_options(&config, self.copied_table_options());
+        let urls = self.table_urls(path, &listing.file_extension).await?;
+        Ok(options
+            .get_resolved_schema(&config, self.state(), urls[0].clone())
+            .await?)
+    }
+
+    async fn table_urls(&self, path: &str, ext: &str) -> anyhow::Result<Vec<ListingTableUrl>> {
+        match listing::glob_files(&self.state(), path, ext).await? {
+            Some(urls) => Ok(urls),
+            None => Ok(vec![ListingTableUrl::parse(path)?]),
+        }
+    }
+
+    async fn partition_cols(
+        &self,
+        file_opts: &FileOpts,
+    ) -> anyhow::Result<Vec<(String, DataType)>> {
+        listing::partition_cols(&self.state(), &file_opts.filename, &file_opts.ext).await
+    }
+
+    fn parse_sql(&self, sql: &str) -> Option<Statement> {
+        let state = self.state();
+        state
+            .sql_to_statement(sql, &state.config_options().sql_parser.dialect)
+            .ok()
+    }
+
+    fn table_references(&self, statement: &Statement) -> Vec<TableReference> {
+        self.state()
+            .resolve_table_references(statement)
+            .unwrap_or_default()
+    }
+
+    // saved views also need the datasets they select from; a saved dataset that fails to connect
+    // is reported, and left for the statement to fail on if it really needs it
+    async fn connect_used(&mut self, tables: &[TableReference]) {
+        let mut tables = tables.to_vec();
+        let mut used = HashSet::new();
+        while let Some(table) = tables.pop() {
+            // a table of this session is never one of a saved dataset
+            if self.table_exist(table.clone()).unwrap_or(false) {
+                continue;
+            }
+            for name in &self.saved {
+                if !self.is_used(name, &table) || !used.insert(name.clone()) {
+                    continue;
+                }
+                if let Some(statement) =
+                    self.catalog.view(name).and_then(|v| self.parse_sql(&v.sql))
+                {
+                    tables.extend(self.table_references(&statement));
+                }
+            }
+        }
+        // in catalog order, so views come after what they select from
+        let used = self
+            .catalog
+            .names()
+            .filter(|name| used.contains(*name))
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        for name in used {
+            if let Err(e) = self.connect_saved(&name).await {
+                eprintln!("{}", e);
+            }
+        }
+    }
+
+    // a table is a dataset or view, a sheet of a saved workbook, or a table in the schema of a
+    // database or warehouse
+    fn is_used(&self, name: &str, table: &TableReference) -> bool {
+        match table.schema() {
+            Some(schema) => schema == name,
+            None => {
+                table.table() == name
+                    || self
+                        .catalog
+                        .get(name)
+                        .is_some_and(|entry| entry.sheets.iter().any(|s| s == table.table()))
+            }
+        }
+    }
+
+    fn is_schema(&self, name: &str) -> bool {
+        let catalog = self
+            .state()
+            .config_options()
+            .catalog
+            .default_catalog
+            .clone();
+        self.catalog(&catalog)
+            .is_some_and(|catalog| catalog.schema(name).is_some())
+    }
+
+    async fn connect_saved(&mut self, name: &str) -> anyhow::Result<()> {
+        if let Some(view) = self.catalog.view(name) {
+            let sql = view.sql.clone();
+            self.ctx.sql(&sql).await.map_err(|e| {
+                anyhow!(
+                    "Failed to create saved view {}, forget it if it no longer works: {}",
+                    name,
+                    e
+                )
+            })?;
+            self.saved.remove(name);
+            return Ok(());
+        }
+        let Some(entry) = self.catalog.get(name) else {
+            return Ok(());
+        };
+        let result = match entry.opts() {
+            Ok(opts) => self.connect(&opts).await,
+            Err(e) => Err(e),
+        };
+        result.map_err(|e| {
+            anyhow!(
+                "Failed to connect saved dataset {}, forget it if it is gone: {}",
+                name,
+                e
+            )
+        })
+    }
+
+    async fn register_dataset(
+        &mut self,
+        conn: &DataSetConn,
+        opts: &ConnectOpts,
+    ) -> anyhow::Result<()> {
+        match conn {
             DataSetConn::Postgres(conn_str) => {
-                println!("Connecting to {}", conn_str)
+                let client = postgres::connect_client(conn_str).await?;
+                match &opts.table {
+                    Some(table) => {
+                        let provider = PostgresTable::try_new(client, table).await?;
+                        self.register_table(&opts.name, Arc::new(provider))?;
+                    }
+                    None => {
+                        let schema = MemorySchemaProvider::new();
+                        for table in postgres::list_tables(&client, opts.views).await? {
+                            let provider = PostgresTable::try_new(client.clone(), &table).await?;
+                            schema.register_table(table, Arc::new(provider))?;
+                        }
+                        self.register_schema(&opts.name, Arc::new(schema))?;
+                    }
+                }
+            }
+            DataSetConn::MySql(conn_str) => {
+                let pool = mysql::connect_pool(conn_str)?;
+                match &opts.table {
+                    Some(table) => {
+                        let provider = MySqlTable::try_new(pool, table).await?;
+                        self.register_table(&opts.name, Arc::new(provider))?;
+                    }
+                    None => {
+                        let schema = MemorySchemaProvider::new();
+                        for table in mysql::list_tables(&pool, opts.views).await? {
+                            let provider = MySqlTable::try_new(pool.clone(), &table).await?;
+                            schema.register_table(table, Arc::new(provider))?;
+                        }
+                        self.register_schema(&opts.name, Arc::new(schema))?;
+                    }
+                }
             }
+            DataSetConn::Sqlite(path) => match &opts.table {
+                Some(table) => {
+                    let provider = SqliteTable::try_new(path, table).await?;
+                    self.register_table(&opts.name, Arc::new(provider))?;
+                }
+                None => {
+                    let schema = MemorySchemaProvider::new();
+                    for table in sqlite::list_tables(path, opts.views).await? {
+                        let provider = SqliteTable::try_new(path, &table).await?;
+                        schema.register_table(table, Arc::new(provider))?;
+                    }
+                    self.register_schema(&opts.name, Arc::new(schema))?;
+                }
+            },
             DataSetConn::Csv(file_opts) => {
-                let csv_opts = CsvReadOptions {
+                let mut csv_opts = CsvReadOptions {
+                    has_header: !opts.read.no_header,
+                    delimiter: opts.read.delimiter(file_opts),
+                    quote: opts.read.quote,
+                    escape: opts.read.escape,
+                    comment: opts.read.comment,
+                    schema: opts.read.schema.as_deref(),
+                    schema_infer_max_records: opts.read.schema_infer_rows(),
                     file_extension: &file_opts.ext,
                     file_compression_type: file_opts.compression,
+                    table_partition_cols: self.partition_cols(file_opts).await?,
                     ..Default::default()
                 };
-                self.register_csv(&opts.name, &file_opts.filename, csv_opts)
-                    .await?;
+                if let Some(schema) = opts.read.schema.as_ref().filter(|_| !opts.read.no_header) {
+                    let header = CsvReadOptions {
+                        schema: None,
+                        schema_infer_max_records: 0,
+                        ..csv_opts.clone()
+                    };
+                    let file = self.file_schema(&file_opts.filename, header).await?;
+                    check_schema(schema, &file, true)?;
+                }
+                // a column holding the null marker can't be read as the type it's cast to later
+                let schema = match (&opts.read.null_value, &opts.read.schema) {
+                    (Some(_), Some(schema)) => Some(csv::string_schema(schema)),
+                    (None, Some(_)) => None,
+                    // datafusion infers the schema without the quote and escape characters
+                    (None, None) if !csv::is_quoted(&opts.read) => None,
+                    (_, None) => {
+                        let listing = csv_opts
+                            .to_listing_options(&self.copied_config(), self.copied_table_options());
+                        let urls = self.table_urls(&file_opts.filename, &file_opts.ext).await?;
+                        let state = self.state();
+                        Some(
+                            csv::infer_schema(&state, &listing, &urls, &opts.read, file_opts)
+                                .await?,
+                        )
+                    }
+                };
+                if let Some(schema) = &schema {
+                    csv_opts.schema = Some(schema);
+                }
+                let table = self.listing_table(&file_opts.filename, csv_opts).await?;
+                let table = match &opts.read.null_value {
+                    Some(null_value) => {
+                        csv::null_values(self, table, null_value, opts.read.schema.as_ref())?
+                    }
+                    None => table,
+                };
+                self.register_table(&opts.name, table)?;
             }
             DataSetConn::NdJson(file_opts) => {
-                let json_opts = NdJsonReadOptions {
+                let document = opts.read.json_path.is_some()
+                    || opts.read.flatten
+                    || json::is_document(&self.state(), file_opts).await;
+                if document {
+                    let table = json::read_json(&self.state(), file_opts, &opts.read).await?;
+                    self.register_table(&opts.name, Arc::new(table))?;
+                } else {
+                    let json_opts = NdJsonReadOptions {
+                        schema: opts.read.schema.as_deref(),
+                        schema_infer_max_records: opts.read.schema_infer_rows(),
+                        file_extension: &file_opts.ext,
+                        file_compression_type: file_opts.compression,
+                        table_partition_cols: self.partition_cols(file_opts).await?,
+                        ..Default::default()
+                    };
+                    if let Some(schema) = &opts.read.schema {
+                        let inferred = NdJsonReadOptions {
+                            schema: None,
+                            ..json_opts.clone()
+                        };
+                        let file = self.file_schema(&file_opts.filename, inferred).await?;
+                        check_schema(schema, &file, false)?;
+                    }
+                    self.register_files(&opts.name, &file_opts.filename, json_opts)
+                        .await?;
+                }
+            }
+            DataSetConn::Parquet(file_opts) => {
+                let parquet_opts = ParquetReadOptions {
                     file_extension: &file_opts.ext,
-                    file_compression_type: file_opts.compression,
+                    table_partition_cols: self.partition_cols(file_opts).await?,
                     ..Default::default()
                 };
-                self.register_json(&opts.name, &file_opts.filename, json_opts)
+                self.register_files(&opts.name, &file_opts.filename, parquet_opts)
                     .await?;
             }
-            DataSetConn::Parquet(filename) => {
-                self.register_parquet(&opts.name, filename, Default::default())
-                    .await?;
+            DataSetConn::Arrow(file_opts) => {
+                let is_file = Path::new(&file_opts.filename).is_file();
+                if is_file && !ipc::is_file_format(&file_opts.filename).await? {
+                    let table = ipc::read_stream(&file_opts.filename).await?;
+                    self.register_table(&opts.name, Arc::new(table))?;
+                } else {
+                    let arrow_opts = ArrowReadOptions {
+                        file_extension: &file_opts.ext,
+                        table_partition_cols: self.partition_cols(file_opts).await?,
+                        ..Default::default()
+                    };
+                    self.register_files(&opts.name, &file_opts.filename, arrow_opts)
+                        .await?;
+                }
+            }
+            DataSetConn::Excel(path) => match &opts.sheet {
+                Some(sheet) => {
+                    let table = excel::read_sheet(path, sheet)?;
+                    self.register_table(&opts.name, Arc::new(table))?;
+                }
+                // every sheet is read before any is registered, and none stays if one fails
+                None => {
+                    let mut registered = vec![];
+                    for (sheet, table) in excel::read_sheets(path)? {
+                        let name = format!("{}_{}", opts.name, identifier(&sheet));
+                        if let Err(e) = self.register_table(&name, Arc::new(table)) {
+                            for name in registered {
+                                self.deregister_table(&name)?;
+                            }
+                            return Err(e.into());
+                        }
+                        registered.push(name);
+                    }
+                    self.sheets.insert(opts.name.clone(), registered);
+                }
+            },
+            DataSetConn::Avro(file_opts) => {
+                let avro_opts = AvroReadOptions {
+                    file_extension: &file_opts.ext,
+                    table_partition_cols: self.partition_cols(file_opts).await?,
+                    ..Default::default()
+                };
+                let table = self.listing_table(&file_opts.filename, avro_opts).await?;
+                self.register_table(&opts.name, Arc::new(AvroTable(table)))?;
+            }
+            DataSetConn::Delta(path) => {
+                let table =
+                    delta::open_table(&self.state(), path, opts.version, opts.timestamp).await?;
+                self.register_table(&opts.name, Arc::new(table))?;
+            }
+            DataSetConn::Iceberg(path) => {
+                let state = self.state();
+                match &opts.table {
+                    Some(table) => {
+                        let path = iceberg::table_path(path, table);
+                        let table =
+                            iceberg::open_table(&state, &path, opts.snapshot_id, opts.timestamp)
+                                .await?;
+                        self.register_table(&opts.name, Arc::new(table))?;
+                    }
+                    None if iceberg::is_table(&state, path).await? => {
+                        let table =
+                            iceberg::open_table(&state, path, opts.snapshot_id, opts.timestamp)
+                                .await?;
+                        self.register_table(&opts.name, Arc::new(table))?;
+                    }
+                    None => {
+                        if opts.snapshot_id.is_some() {
+                            return Err(anyhow!(
+                                "Snapshot id needs a single iceberg table, use --table to pick one"
+                            ));
+                        }
+                        let schema = MemorySchemaProvider::new();
+                        for name in iceberg::list_tables(&state, path).await? {
+                            let table_path = iceberg::table_path(path, &name);
+                            let table =
+                                iceberg::open_table(&state, &table_path, None, opts.timestamp)
+                                    .await?;
+                            schema.register_table(name, Arc::new(table))?;
+                        }
+                        self.register_schema(&opts.name, Arc::new(schema))?;
+                    }
+                }
+            }
+            DataSetConn::Text(file_opts) => {
+                let table =
+                    text::read_text(&self.state(), file_opts, &opts.text, &opts.read).await?;
+                self.register_table(&opts.name, Arc::new(table))?;
+            }
+            DataSetConn::Stdin(format) => {
+                let table = stdin::read_stdin(*format, &opts.read, &opts.text)?;
+                self.register_table(&opts.name, Arc::new(table))?;
+            }
+            DataSetConn::Unknown(file_opts) => {
+                return Err(anyhow!(
+                    "Unknown format of dataset: {}, use --format to specify it",
+                    file_opts.filename
+                ));
             }
         }
+        Ok(())
+    }
 
+    fn register_schema(&self, name: &str, schema: Arc<dyn SchemaProvider>) -> anyhow::Result<()> {
+        let catalog_name = self
+            .state()
+            .config_options()
+            .catalog
+            .default_catalog
+            .clone();
+        let catalog = self
+            .catalog(&catalog_name)
+            .ok_or_else(|| anyhow!("Catalog not found: {}", catalog_name))?;
+        if catalog.schema(name).is_some() {
+            return Err(anyhow!("Schema already exists: {}", name));
+        }
+        catalog.register_schema(name, schema)?;
         Ok(())
     }
 
-    async fn list(&self) -> anyhow::Result<impl ReplDisplay> {
-        let sql = "select table_name,table_type from information_schema.tables where table_schema = 'public'";
-        let df = self.0.sql(sql).await?;
-        Ok(df)
+    // a dataset is a table, the sheets of a workbook or, for databases and warehouses, a schema
+    // of tables; once the provider is dropped the batches of memory tables are freed and
+    // connections closed
+    fn take_dataset(&mut self, name: &str) -> anyhow::Result<Option<Dataset>> {
+        if let Some(names) = self.sheets.remove(name) {
+            let mut sheets = vec![];
+            for name in names {
+                if let Some(table) = self.deregister_table(&name)? {
+                    sheets.push((name, table));
+                }
+            }
+            return Ok(Some(Dataset::Sheets(sheets)));
+        }
+        if let Some(table) = self.deregister_table(name)? {
+            return Ok(Some(Dataset::Table(table)));
+        }
+        let options = self.state().config_options().catalog.clone();
+        if name == options.default_schema || name == "information_schema" {
+            return Err(anyhow!("Schema can't be disconnected: {}", name));
+        }
+        let catalog = self
+            .catalog(&options.default_catalog)
+            .ok_or_else(|| anyhow!("Catalog not found: {}", options.default_catalog))?;
+        Ok(catalog.deregister_schema(name, true)?.map(Dataset::Schema))
     }
 
-    async fn schema(&self, name: &str) -> anyhow::Result<impl ReplDisplay> {
-        let df = self.0.sql(&format!("DESCRIBE {}", name)).await?;
-        Ok(df)
+    fn restore_dataset(&mut self, name: &str, dataset: Dataset) -> anyhow::Result<()> {
+        match dataset {
+            Dataset::Table(table) => {
+                self.register_table(name, table)?;
+            }
+            Dataset::Schema(schema) => self.register_schema(name, schema)?,
+            Dataset::Sheets(sheets) => {
+                let mut names = vec![];
+                for (sheet, table) in sheets {
+                    self.register_table(&sheet, table)?;
+                    names.push(sheet);
+                }
+                self.sheets.insert(name.to_string(), names);
+            }
+        }
+        Ok(())
     }
-    async fn describe(&self, name: &str) -> anyhow::Result<impl ReplDisplay> {
-        let df = self.0.sql(&format!("select * from {}", name)).await?;
-        let ddf = DataFrameDescriber::try_new(df)?;
-        ddf.describe().await
+}
+
+// a schema replaces the one of the file: csv columns are taken by position, so it must list them
+// all in order, json ones by name, so each must be in the file
+fn check_schema(given: &Schema, file: &Schema, in_order: bool) -> anyhow::Result<()> {
+    let names = |schema: &Schema| {
+        schema
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>()
+    };
+    let (given, file) = (names(given), names(file));
+    if let Some(name) = given.iter().find(|name| !file.contains(name)) {
+        return Err(anyhow!(
+            "Column {} of the schema not found, the columns are: {}",
+            name,
+            file.join(", ")
+        ));
     }
-    async fn head(&self, name: &str, n: usize) -> anyhow::Result<impl ReplDisplay> {
-        let df = self
-            .0
-            .sql(&format!("SELECT * FROM {} LIMIT {}", name, n))
-            .await?;
-        Ok(df)
+    if in_order && given != file {
+        return Err(anyhow!(
+            "The schema must list every column in order: {}",
+            file.join(", ")
+        ));
     }
-    async fn sql(&self, sql: &str) -> anyhow::Result<impl ReplDisplay> {
-        let df = self.0.sql(sql).await?;
-        Ok(df)
+    Ok(())
+}
+
+fn view_change(statement: &Statement) -> Option<ViewChange> {
+    let Statement::Statement(statement) = statement else {
+        return None;
+    };
+    match statement.as_ref() {
+        SqlStatement::CreateView { name, .. } => Some(ViewChange::Create(name.to_string())),
+        SqlStatement::Drop {
+            object_type: ObjectType::View,
+            names,
+            ..
+        } => Some(ViewChange::Drop(
+            names.iter().map(|name| name.to_string()).collect(),
+        )),
+        _ => None,
     }
 }
 
@@ -93,7 +971,7 @@ impl Deref for DataFusionBackend {
     type Target = SessionContext;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.ctx
     }
 }
 
@@ -0,0 +1,348 @@
+use std::{any::Any, sync::Arc};
+
+use anyhow::anyhow;
+use arrow::{
+    array::{
+        ArrayRef, BinaryArray, BooleanArray, Date32Array, Float64Array, Int64Array, RecordBatch,
+        RecordBatchOptions, StringArray, TimestampMicrosecondArray, UInt64Array,
+    },
+    datatypes::{DataType, Date32Type, Field, Schema, SchemaRef, TimeUnit},
+};
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveDateTime};
+use datafusion::{
+    catalog::Session,
+    common::project_schema,
+    datasource::{TableProvider, TableType},
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    logical_expr::TableProviderFilterPushDown,
+    physical_plan::{
+        stream::RecordBatchReceiverStream,
+        streaming::{PartitionStream, StreamingTableExec},
+        ExecutionPlan,
+    },
+    prelude::Expr,
+    sql::unparser::dialect::MySqlDialect,
+};
+use mysql_async::{prelude::Queryable, Pool, Row, Value};
+
+use super::remote::{filter_pushdown, quote_ident, quote_table, select_sql};
+
+const BATCH_SIZE: usize = 8192;
+
+#[derive(Debug)]
+struct MySqlColumn {
+    name: String,
+    // columns without a native arrow mapping are never filtered remotely, nor are strings, whose
+    // default collations ignore case, accents and trailing spaces, or tinyint(1) read as booleans
+    filterable: bool,
+}
+
+pub struct MySqlTable {
+    pool: Pool,
+    table: String,
+    table_type: TableType,
+    columns: Vec<MySqlColumn>,
+    schema: SchemaRef,
+}
+
+pub fn connect_pool(conn_str: &str) -> anyhow::Result<Pool> {
+    Ok(Pool::from_url(conn_str)?)
+}
+
+pub async fn list_tables(pool: &Pool, views: bool) -> anyhow::Result<Vec<String>> {
+    let sql = if views {
+        "SELECT TABLE_NAME FROM information_schema.TABLES \
+         WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE IN ('BASE TABLE', 'VIEW') \
+         ORDER BY TABLE_NAME"
+    } else {
+        "SELECT TABLE_NAME FROM information_schema.TABLES \
+         WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE' \
+         ORDER BY TABLE_NAME"
+    };
+    let mut conn = pool.get_conn().await?;
+    Ok(conn.query::<String, _>(sql).await?)
+}
+
+impl MySqlTable {
+    pub async fn try_new(pool: Pool, table: &str) -> anyhow::Result<Self> {
+        let (table_schema, table_name) = match table.split_once('.') {
+            Some((schema, name)) => (Some(schema.to_string()), name.to_string()),
+            None => (None, table.to_string()),
+        };
+        let mut conn = pool.get_conn().await?;
+        let rows: Vec<(String, String, String, String, String)> = conn
+            .exec(
+                "SELECT c.COLUMN_NAME, c.DATA_TYPE, c.COLUMN_TYPE, c.IS_NULLABLE, t.TABLE_TYPE \
+                 FROM information_schema.COLUMNS c \
+                 JOIN information_schema.TABLES t \
+                 ON c.TABLE_SCHEMA = t.TABLE_SCHEMA AND c.TABLE_NAME = t.TABLE_NAME \
+                 WHERE c.TABLE_SCHEMA = COALESCE(?, DATABASE()) AND c.TABLE_NAME = ? \
+                 ORDER BY c.ORDINAL_POSITION",
+                (table_schema, table_name),
+            )
+            .await?;
+        drop(conn);
+        if rows.is_empty() {
+            return Err(anyhow!("MySQL table not found: {}", table));
+        }
+
+        let table_type = match rows[0].4.as_str() {
+            "VIEW" => TableType::View,
+            _ => TableType::Base,
+        };
+        let mut columns = Vec::with_capacity(rows.len());
+        let mut fields = Vec::with_capacity(rows.len());
+        for (name, data_type, column_type, nullable, _) in rows {
+            let (data_type, native) = mysql_to_arrow(&data_type, &column_type);
+            let filterable = native && !matches!(data_type, DataType::Utf8 | DataType::Boolean);
+            fields.push(Field::new(&name, data_type, nullable == "YES"));
+            columns.push(MySqlColumn { name, filterable });
+        }
+
+        Ok(Self {
+            pool,
+            table: quote_table(&MySqlDialect {}, table),
+            table_type,
+            columns,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    fn is_filterable(&self, name: &str) -> bool {
+        self.columns
+            .iter()
+            .any(|column| column.name == name && column.filterable)
+    }
+}
+
+#[async_trait]
+impl TableProvider for MySqlTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.table_type
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = project_schema(&self.schema, projection)?;
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|f| quote_ident(&MySqlDialect {}, f.name()))
+            .collect::<Vec<_>>();
+        let sql = select_sql(&MySqlDialect {}, &self.table, &columns, filters, limit)
+            .map_err(|e| DataFusionError::External(e.into()))?;
+        let partition = Arc::new(MySqlPartition {
+            pool: self.pool.clone(),
+            sql,
+            schema: schema.clone(),
+        });
+        Ok(Arc::new(StreamingTableExec::try_new(
+            schema,
+            vec![partition],
+            None,
+            vec![],
+            false,
+            None,
+        )?))
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(filters
+            .iter()
+            .map(|f| {
+                filter_pushdown(
+                    &MySqlDialect {},
+                    f,
+                    |name| self.is_filterable(name),
+                    |_| true,
+                )
+            })
+            .collect())
+    }
+}
+
+// the query runs when the plan is executed, and its rows are handed on in batches as they arrive
+struct MySqlPartition {
+    pool: Pool,
+    sql: String,
+    schema: SchemaRef,
+}
+
+impl PartitionStream for MySqlPartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let mut builder = RecordBatchReceiverStream::builder(self.schema.clone(), 2);
+        let tx = builder.tx();
+        let pool = self.pool.clone();
+        let sql = self.sql.clone();
+        let schema = self.schema.clone();
+        let external = |e: mysql_async::Error| DataFusionError::External(Box::new(e));
+        builder.spawn(async move {
+            let mut conn = pool.get_conn().await.map_err(external)?;
+            // prepared statements use the binary protocol, which keeps values typed
+            let mut result = conn.exec_iter(sql, ()).await.map_err(external)?;
+            let mut rows = Vec::with_capacity(BATCH_SIZE);
+            loop {
+                let row = result.next().await.map_err(external)?;
+                let done = row.is_none();
+                rows.extend(row.map(Row::unwrap));
+                if rows.len() == BATCH_SIZE || done && !rows.is_empty() {
+                    let batch = rows_to_batch(schema.clone(), &std::mem::take(&mut rows))
+                        .map_err(|e| DataFusionError::External(e.into()))?;
+                    // nothing reads the rows anymore, e.g. once a limit is reached
+                    if tx.send(Ok(batch)).await.is_err() {
+                        break;
+                    }
+                }
+                if done {
+                    break;
+                }
+            }
+            Ok(())
+        });
+        builder.build()
+    }
+}
+
+fn mysql_to_arrow(data_type: &str, column_type: &str) -> (DataType, bool) {
+    match data_type.to_lowercase().as_str() {
+        "tinyint" if column_type.to_lowercase() == "tinyint(1)" => (DataType::Boolean, true),
+        "bigint" if column_type.to_lowercase().contains("unsigned") => (DataType::UInt64, true),
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "year" => {
+            (DataType::Int64, true)
+        }
+        "float" | "double" | "real" | "decimal" | "numeric" => (DataType::Float64, true),
+        "char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" | "enum" | "set" => {
+            (DataType::Utf8, true)
+        }
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => {
+            (DataType::Binary, true)
+        }
+        "date" => (DataType::Date32, true),
+        "datetime" | "timestamp" => (DataType::Timestamp(TimeUnit::Microsecond, None), true),
+        _ => (DataType::Utf8, false),
+    }
+}
+
+fn rows_to_batch(schema: SchemaRef, rows: &[Vec<Value>]) -> anyhow::Result<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| column_to_array(rows, idx, field.data_type()))
+        .collect::<Vec<_>>();
+    let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+    Ok(RecordBatch::try_new_with_options(
+        schema, columns, &options,
+    )?)
+}
+
+fn column_to_array(rows: &[Vec<Value>], idx: usize, data_type: &DataType) -> ArrayRef {
+    let values = rows.iter().map(|row| &row[idx]);
+    match data_type {
+        DataType::Boolean => Arc::new(BooleanArray::from_iter(values.map(|v| match v {
+            Value::Int(v) => Some(*v != 0),
+            Value::UInt(v) => Some(*v != 0),
+            v => parse_bytes::<i64>(v).map(|v| v != 0),
+        }))),
+        DataType::Int64 => Arc::new(Int64Array::from_iter(values.map(|v| match v {
+            Value::Int(v) => Some(*v),
+            Value::UInt(v) => Some(*v as i64),
+            v => parse_bytes(v),
+        }))),
+        DataType::UInt64 => Arc::new(UInt64Array::from_iter(values.map(|v| match v {
+            Value::Int(v) => Some(*v as u64),
+            Value::UInt(v) => Some(*v),
+            v => parse_bytes(v),
+        }))),
+        DataType::Float64 => Arc::new(Float64Array::from_iter(values.map(|v| match v {
+            Value::Int(v) => Some(*v as f64),
+            Value::UInt(v) => Some(*v as f64),
+            Value::Float(v) => Some(*v as f64),
+            Value::Double(v) => Some(*v),
+            v => parse_bytes(v),
+        }))),
+        DataType::Binary => Arc::new(BinaryArray::from_iter(values.map(|v| match v {
+            Value::Bytes(v) => Some(v.clone()),
+            _ => None,
+        }))),
+        DataType::Date32 => {
+            Arc::new(Date32Array::from_iter(values.map(|v| {
+                to_datetime(v).map(|v| Date32Type::from_naive_date(v.date()))
+            })))
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            Arc::new(TimestampMicrosecondArray::from_iter(
+                values.map(|v| to_datetime(v).map(|v| v.and_utc().timestamp_micros())),
+            ))
+        }
+        _ => Arc::new(StringArray::from_iter(values.map(|v| match v {
+            Value::NULL => None,
+            Value::Bytes(v) => Some(String::from_utf8_lossy(v).into_owned()),
+            Value::Int(v) => Some(v.to_string()),
+            Value::UInt(v) => Some(v.to_string()),
+            Value::Float(v) => Some(v.to_string()),
+            Value::Double(v) => Some(v.to_string()),
+            Value::Date(..) => to_datetime(v).map(|v| v.to_string()),
+            Value::Time(neg, days, hours, minutes, seconds, micros) => Some(format!(
+                "{}{:02}:{:02}:{:02}.{:06}",
+                if *neg { "-" } else { "" },
+                *days * 24 + *hours as u32,
+                minutes,
+                seconds,
+                micros
+            )),
+        }))),
+    }
+}
+
+fn parse_bytes<T: std::str::FromStr>(value: &Value) -> Option<T> {
+    match value {
+        Value::Bytes(v) => std::str::from_utf8(v).ok()?.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// zero dates such as `0000-00-00` are not valid and come back as nulls
+fn to_datetime(value: &Value) -> Option<NaiveDateTime> {
+    match value {
+        Value::Date(year, month, day, hour, minute, second, micros) => NaiveDate::from_ymd_opt(
+            *year as i32,
+            *month as u32,
+            *day as u32,
+        )?
+        .and_hms_micro_opt(*hour as u32, *minute as u32, *second as u32, *micros),
+        Value::Bytes(v) => {
+            let v = std::str::from_utf8(v).ok()?;
+            NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .or_else(|| {
+                    NaiveDate::parse_from_str(v, "%Y-%m-%d")
+                        .ok()
+                        .and_then(|d| d.and_hms_opt(0, 0, 0))
+                })
+        }
+        _ => None,
+    }
+}
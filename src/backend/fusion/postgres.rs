@@ -0,0 +1,367 @@
+use std::{any::Any, sync::Arc};
+
+use anyhow::anyhow;
+use arrow::{
+    array::{
+        ArrayRef, BinaryArray, BooleanArray, Date32Array, Float32Array, Float64Array, Int16Array,
+        Int32Array, Int64Array, RecordBatch, RecordBatchOptions, StringArray,
+        TimestampMicrosecondArray,
+    },
+    datatypes::{DataType, Date32Type, Field, Schema, SchemaRef, TimeUnit},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use datafusion::{
+    catalog::Session,
+    common::project_schema,
+    datasource::{TableProvider, TableType},
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    logical_expr::TableProviderFilterPushDown,
+    physical_plan::{
+        stream::RecordBatchStreamAdapter,
+        streaming::{PartitionStream, StreamingTableExec},
+        ExecutionPlan,
+    },
+    prelude::Expr,
+    sql::unparser::dialect::PostgreSqlDialect,
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use rustls::{crypto::ring, ClientConfig, RootCertStore};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_postgres::{config::SslMode, Client, Config, Connection, NoTls, Row};
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+use super::remote::{filter_pushdown, has_ordering, quote_ident, quote_table, select_sql};
+
+const BATCH_SIZE: usize = 8192;
+
+#[derive(Debug)]
+struct PgColumn {
+    name: String,
+    // postgres types without a native arrow mapping are fetched as text
+    cast: Option<&'static str>,
+    udt_name: String,
+}
+
+pub struct PostgresTable {
+    client: Arc<Client>,
+    table: String,
+    table_type: TableType,
+    columns: Vec<PgColumn>,
+    schema: SchemaRef,
+}
+
+// tls is only used when `sslmode=require`, the server certificate is verified against the
+// system roots
+pub async fn connect_client(conn_str: &str) -> anyhow::Result<Arc<Client>> {
+    let config = conn_str.parse::<Config>()?;
+    let client = match config.get_ssl_mode() {
+        SslMode::Require => {
+            let (client, connection) = config.connect(tls_connector()?).await?;
+            spawn_connection(connection);
+            client
+        }
+        _ => {
+            let (client, connection) = config.connect(NoTls).await?;
+            spawn_connection(connection);
+            client
+        }
+    };
+    Ok(Arc::new(client))
+}
+
+fn spawn_connection<S, T>(connection: Connection<S, T>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("Postgres connection error: {}", e);
+        }
+    });
+}
+
+fn tls_connector() -> anyhow::Result<MakeRustlsConnect> {
+    let mut roots = RootCertStore::empty();
+    let certs = rustls_native_certs::load_native_certs();
+    if certs.certs.is_empty() {
+        if let Some(e) = certs.errors.first() {
+            return Err(anyhow!("Failed to load system root certificates: {}", e));
+        }
+    }
+    roots.add_parsable_certificates(certs.certs);
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(MakeRustlsConnect::new(config))
+}
+
+pub async fn list_tables(client: &Client, views: bool) -> anyhow::Result<Vec<String>> {
+    let table_types = if views {
+        vec!["BASE TABLE", "VIEW"]
+    } else {
+        vec!["BASE TABLE"]
+    };
+    let rows = client
+        .query(
+            "SELECT table_name FROM information_schema.tables \
+             WHERE table_schema = current_schema() AND table_type = ANY($1) \
+             ORDER BY table_name",
+            &[&table_types],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|row| row.get(0)).collect())
+}
+
+impl PostgresTable {
+    pub async fn try_new(client: Arc<Client>, table: &str) -> anyhow::Result<Self> {
+        let (table_schema, table_name) = match table.split_once('.') {
+            Some((schema, name)) => (Some(schema), name),
+            None => (None, table),
+        };
+        let rows = client
+            .query(
+                "SELECT c.column_name, c.udt_name, c.is_nullable, t.table_type \
+                 FROM information_schema.columns c \
+                 JOIN information_schema.tables t USING (table_schema, table_name) \
+                 WHERE table_schema = coalesce($1, current_schema()) AND table_name = $2 \
+                 ORDER BY c.ordinal_position",
+                &[&table_schema, &table_name],
+            )
+            .await?;
+        if rows.is_empty() {
+            return Err(anyhow!("Postgres table not found: {}", table));
+        }
+
+        let table_type = match rows[0].get::<_, &str>(3) {
+            "VIEW" => TableType::View,
+            _ => TableType::Base,
+        };
+        let mut columns = Vec::with_capacity(rows.len());
+        let mut fields = Vec::with_capacity(rows.len());
+        for row in rows {
+            let name: String = row.get(0);
+            let udt_name: String = row.get(1);
+            let nullable: String = row.get(2);
+            let (data_type, cast) = pg_to_arrow(&udt_name);
+            fields.push(Field::new(&name, data_type, nullable == "YES"));
+            columns.push(PgColumn {
+                name,
+                cast,
+                udt_name,
+            });
+        }
+
+        Ok(Self {
+            client,
+            table: quote_table(&PostgreSqlDialect {}, table),
+            table_type,
+            columns,
+            schema: Arc::new(Schema::new(fields)),
+        })
+    }
+
+    fn select_column(&self, idx: usize) -> String {
+        let column = &self.columns[idx];
+        let name = quote_ident(&PostgreSqlDialect {}, &column.name);
+        match column.cast {
+            Some(cast) => format!("{}::{} AS {}", name, cast, name),
+            None => name,
+        }
+    }
+
+    // `bpchar` ignores trailing spaces in comparisons, while its values keep them
+    fn is_native(&self, name: &str) -> bool {
+        self.columns.iter().any(|column| {
+            column.name == name && column.cast.is_none() && column.udt_name != "bpchar"
+        })
+    }
+
+    // strings are ordered by the column's collation in postgres, by their bytes in datafusion
+    fn is_collated(&self, name: &str) -> bool {
+        self.columns.iter().any(|column| {
+            column.name == name && matches!(column.udt_name.as_str(), "text" | "varchar")
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for PostgresTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.table_type
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = project_schema(&self.schema, projection)?;
+        let columns = match projection {
+            Some(projection) => projection.iter().map(|i| self.select_column(*i)).collect(),
+            None => (0..self.columns.len())
+                .map(|i| self.select_column(i))
+                .collect::<Vec<_>>(),
+        };
+        let sql = select_sql(&PostgreSqlDialect {}, &self.table, &columns, filters, limit)
+            .map_err(|e| DataFusionError::External(e.into()))?;
+        let partition = Arc::new(PgPartition {
+            client: self.client.clone(),
+            sql,
+            schema: schema.clone(),
+        });
+        Ok(Arc::new(StreamingTableExec::try_new(
+            schema,
+            vec![partition],
+            None,
+            vec![],
+            false,
+            None,
+        )?))
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(filters
+            .iter()
+            .map(|f| {
+                let collated = f.column_refs().iter().any(|c| self.is_collated(&c.name));
+                if collated && has_ordering(f) {
+                    return TableProviderFilterPushDown::Unsupported;
+                }
+                filter_pushdown(
+                    &PostgreSqlDialect {},
+                    f,
+                    |name| self.is_native(name),
+                    |_| true,
+                )
+            })
+            .collect())
+    }
+}
+
+// rows are read as they arrive and handed on in batches, rather than all at once
+struct PgPartition {
+    client: Arc<Client>,
+    sql: String,
+    schema: SchemaRef,
+}
+
+impl PartitionStream for PgPartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let client = self.client.clone();
+        let sql = self.sql.clone();
+        let schema = self.schema.clone();
+        let rows = stream::once(async move {
+            client
+                .query_raw(sql.as_str(), std::iter::empty::<&str>())
+                .await
+        })
+        .try_flatten()
+        .try_chunks(BATCH_SIZE)
+        .map(move |rows| {
+            let rows = rows.map_err(|e| DataFusionError::External(Box::new(e.1)))?;
+            rows_to_batch(schema.clone(), &rows).map_err(|e| DataFusionError::External(e.into()))
+        });
+        Box::pin(RecordBatchStreamAdapter::new(self.schema.clone(), rows))
+    }
+}
+
+fn pg_to_arrow(udt_name: &str) -> (DataType, Option<&'static str>) {
+    match udt_name {
+        "bool" => (DataType::Boolean, None),
+        "int2" => (DataType::Int16, None),
+        "int4" => (DataType::Int32, None),
+        "int8" => (DataType::Int64, None),
+        "float4" => (DataType::Float32, None),
+        "float8" => (DataType::Float64, None),
+        "numeric" => (DataType::Float64, Some("float8")),
+        "text" | "varchar" | "bpchar" | "name" => (DataType::Utf8, None),
+        "bytea" => (DataType::Binary, None),
+        "date" => (DataType::Date32, None),
+        "timestamp" => (DataType::Timestamp(TimeUnit::Microsecond, None), None),
+        "timestamptz" => (
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            None,
+        ),
+        _ => (DataType::Utf8, Some("text")),
+    }
+}
+
+fn rows_to_batch(schema: SchemaRef, rows: &[Row]) -> anyhow::Result<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| column_to_array(rows, idx, field.data_type()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+    Ok(RecordBatch::try_new_with_options(
+        schema, columns, &options,
+    )?)
+}
+
+fn column_to_array(rows: &[Row], idx: usize, data_type: &DataType) -> anyhow::Result<ArrayRef> {
+    macro_rules! values {
+        ($t:ty) => {
+            rows.iter()
+                .map(|row| row.try_get::<_, Option<$t>>(idx))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+    }
+
+    let array: ArrayRef = match data_type {
+        DataType::Boolean => Arc::new(BooleanArray::from(values!(bool))),
+        DataType::Int16 => Arc::new(Int16Array::from(values!(i16))),
+        DataType::Int32 => Arc::new(Int32Array::from(values!(i32))),
+        DataType::Int64 => Arc::new(Int64Array::from(values!(i64))),
+        DataType::Float32 => Arc::new(Float32Array::from(values!(f32))),
+        DataType::Float64 => Arc::new(Float64Array::from(values!(f64))),
+        DataType::Utf8 => Arc::new(StringArray::from(values!(String))),
+        DataType::Binary => Arc::new(BinaryArray::from_iter(values!(Vec<u8>))),
+        DataType::Date32 => Arc::new(Date32Array::from(
+            values!(NaiveDate)
+                .into_iter()
+                .map(|v| v.map(Date32Type::from_naive_date))
+                .collect::<Vec<_>>(),
+        )),
+        DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            Arc::new(TimestampMicrosecondArray::from(
+                values!(NaiveDateTime)
+                    .into_iter()
+                    .map(|v| v.map(|v| v.and_utc().timestamp_micros()))
+                    .collect::<Vec<_>>(),
+            ))
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, tz) => Arc::new(
+            TimestampMicrosecondArray::from(
+                values!(DateTime<Utc>)
+                    .into_iter()
+                    .map(|v| v.map(|v| v.timestamp_micros()))
+                    .collect::<Vec<_>>(),
+            )
+            .with_timezone_opt(tz.clone()),
+        ),
+        dt => return Err(anyhow!("Unsupported postgres column type: {}", dt)),
+    };
+    Ok(array)
+}
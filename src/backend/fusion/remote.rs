@@ -0,0 +1,167 @@
+use datafusion::{
+    common::tree_node::{Transformed, TreeNode, TreeNodeRecursion},
+    logical_expr::{BinaryExpr, Expr, Operator, TableProviderFilterPushDown},
+    sql::unparser::{dialect::Dialect, Unparser},
+};
+
+pub fn quote_ident(dialect: &dyn Dialect, ident: &str) -> String {
+    let quote = dialect.identifier_quote_style(ident).unwrap_or('"');
+    let escaped = ident.replace(quote, &format!("{}{}", quote, quote));
+    format!("{}{}{}", quote, escaped, quote)
+}
+
+pub fn quote_table(dialect: &dyn Dialect, table: &str) -> String {
+    table
+        .split('.')
+        .map(|part| quote_ident(dialect, part))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+// only plain comparisons of columns with literals are trusted to give the same rows as datafusion,
+// casts, arithmetic and `like` differ between engines so datafusion checks their rows again
+pub fn filter_pushdown(
+    dialect: &dyn Dialect,
+    filter: &Expr,
+    is_native: impl Fn(&str) -> bool,
+    is_exact: impl Fn(&str) -> bool,
+) -> TableProviderFilterPushDown {
+    let columns = filter.column_refs();
+    let native = columns.iter().all(|column| is_native(&column.name));
+    if !native || !is_pushable(filter) || Unparser::new(dialect).expr_to_sql(filter).is_err() {
+        TableProviderFilterPushDown::Unsupported
+    } else if is_comparison(filter) && columns.iter().all(|column| is_exact(&column.name)) {
+        TableProviderFilterPushDown::Exact
+    } else {
+        TableProviderFilterPushDown::Inexact
+    }
+}
+
+// whether the filter orders values, as `<` or `between` do, which engines do differently for
+// strings, e.g. by a collation rather than by bytes
+pub fn has_ordering(expr: &Expr) -> bool {
+    let mut ordering = false;
+    let _ = expr.apply(|e| {
+        ordering = match e {
+            Expr::Between(_) => true,
+            Expr::BinaryExpr(BinaryExpr { op, .. }) => matches!(
+                op,
+                Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq
+            ),
+            _ => false,
+        };
+        Ok(if ordering {
+            TreeNodeRecursion::Stop
+        } else {
+            TreeNodeRecursion::Continue
+        })
+    });
+    ordering
+}
+
+pub fn select_sql(
+    dialect: &dyn Dialect,
+    table: &str,
+    columns: &[String],
+    filters: &[Expr],
+    limit: Option<usize>,
+) -> anyhow::Result<String> {
+    let columns = if columns.is_empty() {
+        "1".to_string()
+    } else {
+        columns.join(", ")
+    };
+    let mut sql = format!("SELECT {} FROM {}", columns, table);
+
+    let unparser = Unparser::new(dialect);
+    let conditions = filters
+        .iter()
+        .map(|filter| {
+            let expr = unqualify(filter.clone())?;
+            Ok(format!("({})", unparser.expr_to_sql(&expr)?))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if !conditions.is_empty() {
+        sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    if let Some(limit) = limit {
+        sql.push_str(&format!(" LIMIT {}", limit));
+    }
+    Ok(sql)
+}
+
+fn is_pushable(expr: &Expr) -> bool {
+    let mut pushable = true;
+    let _ = expr.apply(|e| {
+        pushable = match e {
+            Expr::Column(_)
+            | Expr::Literal(_)
+            | Expr::Not(_)
+            | Expr::IsNull(_)
+            | Expr::IsNotNull(_)
+            | Expr::Like(_)
+            | Expr::InList(_)
+            | Expr::Between(_)
+            | Expr::Cast(_) => true,
+            Expr::BinaryExpr(BinaryExpr { op, .. }) => matches!(
+                op,
+                Operator::Eq
+                    | Operator::NotEq
+                    | Operator::Lt
+                    | Operator::LtEq
+                    | Operator::Gt
+                    | Operator::GtEq
+                    | Operator::And
+                    | Operator::Or
+                    | Operator::Plus
+                    | Operator::Minus
+                    | Operator::Multiply
+            ),
+            _ => false,
+        };
+        Ok(if pushable {
+            TreeNodeRecursion::Continue
+        } else {
+            TreeNodeRecursion::Stop
+        })
+    });
+    pushable
+}
+
+fn is_comparison(expr: &Expr) -> bool {
+    let is_column = |e: &Expr| matches!(e, Expr::Column(_));
+    let is_literal = |e: &Expr| matches!(e, Expr::Literal(_));
+    match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => match op {
+            Operator::And | Operator::Or => is_comparison(left) && is_comparison(right),
+            Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq => {
+                (is_column(left) && is_literal(right)) || (is_literal(left) && is_column(right))
+            }
+            _ => false,
+        },
+        Expr::Not(e) => is_comparison(e),
+        Expr::IsNull(e) | Expr::IsNotNull(e) => is_column(e),
+        Expr::InList(in_list) => is_column(&in_list.expr) && in_list.list.iter().all(is_literal),
+        Expr::Between(between) => {
+            is_column(&between.expr) && is_literal(&between.low) && is_literal(&between.high)
+        }
+        _ => false,
+    }
+}
+
+// filters reference the registered dataset name, which means nothing to the remote database
+pub fn unqualify(expr: Expr) -> anyhow::Result<Expr> {
+    let expr = expr.transform(|e| match e {
+        Expr::Column(mut column) if column.relation.is_some() => {
+            column.relation = None;
+            Ok(Transformed::yes(Expr::Column(column)))
+        }
+        e => Ok(Transformed::no(e)),
+    })?;
+    Ok(expr.data)
+}
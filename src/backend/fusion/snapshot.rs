@@ -0,0 +1,266 @@
+use std::{any::Any, collections::HashSet, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, AsArray, BooleanArray, RecordBatch, UInt64Array},
+    datatypes::{Field, Schema, SchemaRef},
+};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    common::{Column, ColumnStatistics, DFSchema, ScalarValue},
+    datasource::{
+        listing::PartitionedFile,
+        physical_plan::{FileScanConfig, ParquetExec},
+        schema_adapter::SchemaAdapterFactory,
+        TableProvider, TableType,
+    },
+    error::{DataFusionError, Result},
+    execution::object_store::ObjectStoreUrl,
+    logical_expr::{utils::conjunction, TableProviderFilterPushDown},
+    physical_optimizer::pruning::{PruningPredicate, PruningStatistics},
+    physical_plan::ExecutionPlan,
+    prelude::Expr,
+};
+
+use super::remote::unqualify;
+
+// the parquet data files of one snapshot of a table format such as delta or iceberg, with
+// the partition values and column bounds recorded in its log or manifests
+#[derive(Debug)]
+pub struct SnapshotTable {
+    store_url: ObjectStoreUrl,
+    file_schema: SchemaRef,
+    partition_cols: Vec<Field>,
+    files: Vec<PartitionedFile>,
+    schema: SchemaRef,
+    schema_adapter: Option<Arc<dyn SchemaAdapterFactory>>,
+    row_group_pruning: bool,
+}
+
+impl SnapshotTable {
+    pub fn new(
+        store_url: ObjectStoreUrl,
+        file_schema: SchemaRef,
+        partition_cols: Vec<Field>,
+        files: Vec<PartitionedFile>,
+    ) -> Self {
+        let fields = file_schema
+            .fields()
+            .iter()
+            .map(|f| f.as_ref().clone())
+            .chain(partition_cols.iter().cloned())
+            .collect::<Vec<_>>();
+        Self {
+            store_url,
+            file_schema,
+            partition_cols,
+            files,
+            schema: Arc::new(Schema::new(fields)),
+            schema_adapter: None,
+            row_group_pruning: true,
+        }
+    }
+
+    // for files whose columns are found by something other than their name, e.g. iceberg field
+    // ids; parquet statistics are looked up by name, so they prune row groups only when every
+    // name still means the same column
+    pub fn with_schema_adapter(
+        mut self,
+        schema_adapter: Arc<dyn SchemaAdapterFactory>,
+        row_group_pruning: bool,
+    ) -> Self {
+        self.schema_adapter = Some(schema_adapter);
+        self.row_group_pruning = row_group_pruning;
+        self
+    }
+
+    // evaluates the filters that only use partition columns against each file's partition
+    // values, and all filters against the column bounds of files that have them, so whole files
+    // are skipped without being opened
+    fn prune(&self, state: &dyn Session, filters: &[Expr]) -> Result<Vec<PartitionedFile>> {
+        let mut keep = self.prune_by_statistics(state, filters)?;
+        let filters = filters
+            .iter()
+            .filter(|filter| {
+                filter.column_refs().iter().all(|column| {
+                    self.partition_cols
+                        .iter()
+                        .any(|field| field.name() == &column.name)
+                })
+            })
+            .collect::<Vec<_>>();
+        if filters.is_empty() || self.files.is_empty() {
+            return Ok(self.kept_files(keep));
+        }
+
+        let schema = Arc::new(Schema::new(self.partition_cols.clone()));
+        let columns = (0..self.partition_cols.len())
+            .map(|i| {
+                ScalarValue::iter_to_array(
+                    self.files
+                        .iter()
+                        .map(|file| file.partition_values[i].clone()),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+        let df_schema = DFSchema::try_from(schema)?;
+
+        for filter in filters {
+            let filter =
+                unqualify(filter.clone()).map_err(|e| DataFusionError::External(e.into()))?;
+            let expr = state.create_physical_expr(filter, &df_schema)?;
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows())?;
+            for (keep, value) in keep.iter_mut().zip(result.as_boolean().iter()) {
+                *keep &= value.unwrap_or(false);
+            }
+        }
+        Ok(self.kept_files(keep))
+    }
+
+    fn prune_by_statistics(&self, state: &dyn Session, filters: &[Expr]) -> Result<Vec<bool>> {
+        let keep = vec![true; self.files.len()];
+        if self.files.iter().all(|file| file.statistics.is_none()) {
+            return Ok(keep);
+        }
+        let Some(filter) = conjunction(filters.iter().cloned()) else {
+            return Ok(keep);
+        };
+        let filter = unqualify(filter).map_err(|e| DataFusionError::External(e.into()))?;
+        let df_schema = DFSchema::try_from(self.schema.clone())?;
+        let expr = state.create_physical_expr(filter, &df_schema)?;
+        let statistics = FileStatistics {
+            schema: &self.file_schema,
+            files: &self.files,
+        };
+        PruningPredicate::try_new(expr, self.schema.clone())?.prune(&statistics)
+    }
+
+    fn kept_files(&self, keep: Vec<bool>) -> Vec<PartitionedFile> {
+        self.files
+            .iter()
+            .zip(keep)
+            .filter(|(_, keep)| *keep)
+            .map(|(file, _)| file.clone())
+            .collect()
+    }
+}
+
+// the statistics of each file by column of the file schema, missing ones are unknown
+struct FileStatistics<'a> {
+    schema: &'a Schema,
+    files: &'a [PartitionedFile],
+}
+
+impl FileStatistics<'_> {
+    fn column_values(
+        &self,
+        column: &Column,
+        value: impl Fn(&ColumnStatistics) -> Option<ScalarValue>,
+    ) -> Option<ArrayRef> {
+        let index = self.schema.index_of(&column.name).ok()?;
+        let data_type = self.schema.field(index).data_type();
+        let values = self.files.iter().map(|file| {
+            file.statistics
+                .as_ref()
+                .and_then(|statistics| statistics.column_statistics.get(index))
+                .and_then(&value)
+                .map_or_else(|| ScalarValue::try_from(data_type), Ok)
+        });
+        ScalarValue::iter_to_array(values.collect::<Result<Vec<_>>>().ok()?).ok()
+    }
+}
+
+impl PruningStatistics for FileStatistics<'_> {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.column_values(column, |statistics| {
+            statistics.min_value.get_value().cloned()
+        })
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.column_values(column, |statistics| {
+            statistics.max_value.get_value().cloned()
+        })
+    }
+
+    fn num_containers(&self) -> usize {
+        self.files.len()
+    }
+
+    fn null_counts(&self, column: &Column) -> Option<ArrayRef> {
+        let index = self.schema.index_of(&column.name).ok()?;
+        let counts = self.files.iter().map(|file| {
+            let statistics = file.statistics.as_ref()?.column_statistics.get(index)?;
+            statistics.null_count.get_value().map(|count| *count as u64)
+        });
+        Some(Arc::new(counts.collect::<UInt64Array>()))
+    }
+
+    fn row_counts(&self, _column: &Column) -> Option<ArrayRef> {
+        let counts = self.files.iter().map(|file| match &file.statistics {
+            Some(statistics) => statistics.num_rows.get_value().map(|rows| *rows as u64),
+            None => None,
+        });
+        Some(Arc::new(counts.collect::<UInt64Array>()))
+    }
+
+    fn contained(&self, _column: &Column, _values: &HashSet<ScalarValue>) -> Option<BooleanArray> {
+        None
+    }
+}
+
+#[async_trait]
+impl TableProvider for SnapshotTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let files = self.prune(state, filters)?;
+        let config = FileScanConfig::new(self.store_url.clone(), self.file_schema.clone())
+            .with_file_group(files)
+            .with_table_partition_cols(self.partition_cols.clone())
+            .with_projection(projection.cloned())
+            .with_limit(limit);
+
+        // the remaining filters still prune row groups using the parquet statistics
+        let predicate = match conjunction(filters.iter().cloned()) {
+            Some(filter) if self.row_group_pruning => {
+                let filter = unqualify(filter).map_err(|e| DataFusionError::External(e.into()))?;
+                let df_schema = DFSchema::try_from(self.schema.clone())?;
+                state.create_physical_expr(filter, &df_schema).ok()
+            }
+            _ => None,
+        };
+        let mut builder = ParquetExec::builder(config);
+        if let Some(predicate) = predicate {
+            builder = builder.with_predicate(predicate);
+        }
+        if let Some(schema_adapter) = &self.schema_adapter {
+            builder = builder.with_schema_adapter_factory(schema_adapter.clone());
+        }
+        Ok(builder.build_arc())
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(vec![TableProviderFilterPushDown::Inexact; filters.len()])
+    }
+}
@@ -0,0 +1,343 @@
+use std::{any::Any, sync::Arc};
+
+use anyhow::anyhow;
+use arrow::{
+    array::{
+        ArrayRef, BinaryArray, BooleanArray, Float64Array, Int64Array, RecordBatch,
+        RecordBatchOptions, StringArray,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    common::project_schema,
+    datasource::{TableProvider, TableType},
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    logical_expr::TableProviderFilterPushDown,
+    physical_plan::{
+        stream::RecordBatchReceiverStream,
+        streaming::{PartitionStream, StreamingTableExec},
+        ExecutionPlan,
+    },
+    prelude::Expr,
+    sql::unparser::dialect::SqliteDialect,
+};
+use rusqlite::{types::Value, Connection, OpenFlags};
+use tokio::sync::mpsc::Sender;
+
+use super::remote::{filter_pushdown, quote_ident, quote_table, select_sql};
+
+const BATCH_SIZE: usize = 8192;
+
+#[derive(Debug)]
+struct SqliteColumn {
+    name: String,
+    // columns without a recognised declared type are read as text and never filtered remotely
+    native: bool,
+}
+
+pub struct SqliteTable {
+    path: String,
+    table: String,
+    table_type: TableType,
+    columns: Vec<SqliteColumn>,
+    schema: SchemaRef,
+}
+
+pub async fn list_tables(path: &str, views: bool) -> anyhow::Result<Vec<String>> {
+    let path = path.to_string();
+    tokio::task::spawn_blocking(move || {
+        let conn = open(&path)?;
+        let sql = if views {
+            "SELECT name FROM sqlite_schema WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name"
+        } else {
+            "SELECT name FROM sqlite_schema WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+        };
+        let mut stmt = conn.prepare(sql)?;
+        let tables = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(tables)
+    })
+    .await?
+}
+
+impl SqliteTable {
+    pub async fn try_new(path: &str, table: &str) -> anyhow::Result<Self> {
+        let path = path.to_string();
+        let table = table.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = open(&path)?;
+            let kind: String = conn
+                .query_row(
+                    "SELECT type FROM sqlite_schema WHERE name = ?1",
+                    [&table],
+                    |row| row.get(0),
+                )
+                .map_err(|_| anyhow!("Sqlite table not found: {}", table))?;
+            let table_type = match kind.as_str() {
+                "view" => TableType::View,
+                _ => TableType::Base,
+            };
+
+            let mut stmt =
+                conn.prepare("SELECT name, type, \"notnull\" FROM pragma_table_info(?1)")?;
+            let mut columns = Vec::new();
+            let mut fields = Vec::new();
+            let mut rows = stmt.query([&table])?;
+            while let Some(row) = rows.next()? {
+                let name: String = row.get(0)?;
+                let declared: String = row.get(1)?;
+                let not_null: bool = row.get(2)?;
+                let (data_type, native) = sqlite_to_arrow(&declared);
+                fields.push(Field::new(&name, data_type, !not_null));
+                columns.push(SqliteColumn { name, native });
+            }
+
+            Ok(Self {
+                path,
+                table: quote_table(&SqliteDialect {}, &table),
+                table_type,
+                columns,
+                schema: Arc::new(Schema::new(fields)),
+            })
+        })
+        .await?
+    }
+
+    fn is_native(&self, name: &str) -> bool {
+        self.columns
+            .iter()
+            .any(|column| column.name == name && column.native)
+    }
+}
+
+#[async_trait]
+impl TableProvider for SqliteTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.table_type
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let schema = project_schema(&self.schema, projection)?;
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|f| quote_ident(&SqliteDialect {}, f.name()))
+            .collect::<Vec<_>>();
+        let sql = select_sql(&SqliteDialect {}, &self.table, &columns, filters, limit)
+            .map_err(|e| DataFusionError::External(e.into()))?;
+        let partition = Arc::new(SqlitePartition {
+            path: self.path.clone(),
+            sql,
+            schema: schema.clone(),
+        });
+        Ok(Arc::new(StreamingTableExec::try_new(
+            schema,
+            vec![partition],
+            None,
+            vec![],
+            false,
+            None,
+        )?))
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        // sqlite compares values of any type stored in a column, so every row is checked again
+        Ok(filters
+            .iter()
+            .map(|f| filter_pushdown(&SqliteDialect {}, f, |name| self.is_native(name), |_| false))
+            .collect())
+    }
+}
+
+// the query runs when the plan is executed, and its rows are handed on in batches as they're read
+struct SqlitePartition {
+    path: String,
+    sql: String,
+    schema: SchemaRef,
+}
+
+impl PartitionStream for SqlitePartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let mut builder = RecordBatchReceiverStream::builder(self.schema.clone(), 2);
+        let tx = builder.tx();
+        let path = self.path.clone();
+        let sql = self.sql.clone();
+        let schema = self.schema.clone();
+        builder.spawn_blocking(move || {
+            send_rows(&path, &sql, schema, tx).map_err(|e| DataFusionError::External(e.into()))
+        });
+        builder.build()
+    }
+}
+
+fn send_rows(
+    path: &str,
+    sql: &str,
+    schema: SchemaRef,
+    tx: Sender<Result<RecordBatch>>,
+) -> anyhow::Result<()> {
+    let conn = open(path)?;
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query([])?;
+    let width = schema.fields().len();
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    loop {
+        let row = rows.next()?;
+        let done = row.is_none();
+        if let Some(row) = row {
+            batch.push(
+                (0..width)
+                    .map(|i| row.get::<_, Value>(i))
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+        }
+        if batch.len() == BATCH_SIZE || done && !batch.is_empty() {
+            let batch = rows_to_batch(schema.clone(), &std::mem::take(&mut batch))?;
+            // nothing reads the rows anymore, e.g. once a limit is reached
+            if tx.blocking_send(Ok(batch)).is_err() {
+                break;
+            }
+        }
+        if done {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn open(path: &str) -> anyhow::Result<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    Ok(conn)
+}
+
+// follows sqlite's column affinity rules: https://www.sqlite.org/datatype3.html
+fn sqlite_to_arrow(declared: &str) -> (DataType, bool) {
+    let declared = declared.to_uppercase();
+    if declared.contains("INT") {
+        (DataType::Int64, true)
+    } else if declared.contains("CHAR") || declared.contains("CLOB") || declared.contains("TEXT") {
+        (DataType::Utf8, true)
+    } else if declared.contains("BLOB") {
+        (DataType::Binary, true)
+    } else if declared.contains("REAL")
+        || declared.contains("FLOA")
+        || declared.contains("DOUB")
+        || declared.contains("NUMERIC")
+        || declared.contains("DECIMAL")
+    {
+        (DataType::Float64, true)
+    } else if declared.contains("BOOL") {
+        (DataType::Boolean, true)
+    } else {
+        (DataType::Utf8, false)
+    }
+}
+
+fn rows_to_batch(schema: SchemaRef, rows: &[Vec<Value>]) -> anyhow::Result<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| column_to_array(rows, idx, field))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+    Ok(RecordBatch::try_new_with_options(
+        schema, columns, &options,
+    )?)
+}
+
+// sqlite is dynamically typed, so values are coerced to the column's declared type, and a value
+// that can't be is an error rather than a null
+fn column_to_array(rows: &[Vec<Value>], idx: usize, field: &Field) -> anyhow::Result<ArrayRef> {
+    let values = rows.iter().map(|row| &row[idx]);
+    let mismatch = |v: &Value| {
+        anyhow!(
+            "Sqlite column {} has a value that isn't {}: {:?}",
+            field.name(),
+            field.data_type(),
+            v
+        )
+    };
+    let array: ArrayRef = match field.data_type() {
+        DataType::Int64 => Arc::new(Int64Array::from(
+            values
+                .map(|v| match v {
+                    Value::Null => Ok(None),
+                    Value::Integer(i) => Ok(Some(*i)),
+                    Value::Real(r) if r.fract() == 0.0 => Ok(Some(*r as i64)),
+                    Value::Text(t) => t.trim().parse().map(Some).map_err(|_| mismatch(v)),
+                    v => Err(mismatch(v)),
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        )),
+        DataType::Float64 => Arc::new(Float64Array::from(
+            values
+                .map(|v| match v {
+                    Value::Null => Ok(None),
+                    Value::Integer(i) => Ok(Some(*i as f64)),
+                    Value::Real(r) => Ok(Some(*r)),
+                    Value::Text(t) => t.trim().parse().map(Some).map_err(|_| mismatch(v)),
+                    v => Err(mismatch(v)),
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        )),
+        DataType::Boolean => Arc::new(BooleanArray::from(
+            values
+                .map(|v| match v {
+                    Value::Null => Ok(None),
+                    Value::Integer(i) => Ok(Some(*i != 0)),
+                    Value::Real(r) => Ok(Some(*r != 0.0)),
+                    Value::Text(t) => match t.trim().to_lowercase().as_str() {
+                        "true" | "t" | "1" => Ok(Some(true)),
+                        "false" | "f" | "0" => Ok(Some(false)),
+                        _ => Err(mismatch(v)),
+                    },
+                    v => Err(mismatch(v)),
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        )),
+        DataType::Binary => Arc::new(BinaryArray::from_iter(values.map(|v| match v {
+            Value::Blob(b) => Some(b.clone()),
+            Value::Text(t) => Some(t.as_bytes().to_vec()),
+            Value::Integer(i) => Some(i.to_string().into_bytes()),
+            Value::Real(r) => Some(r.to_string().into_bytes()),
+            Value::Null => None,
+        }))),
+        _ => Arc::new(StringArray::from_iter(values.map(|v| match v {
+            Value::Integer(v) => Some(v.to_string()),
+            Value::Real(v) => Some(v.to_string()),
+            Value::Text(v) => Some(v.clone()),
+            Value::Blob(v) => Some(String::from_utf8_lossy(v).into_owned()),
+            Value::Null => None,
+        }))),
+    };
+    Ok(array)
+}
@@ -0,0 +1,82 @@
+use std::io::{self, Cursor, IsTerminal, Read};
+
+use anyhow::anyhow;
+use arrow::{
+    array::RecordBatchReader,
+    ipc::reader::{FileReader, StreamReader},
+};
+use bytes::Bytes;
+use datafusion::{
+    datasource::{avro_to_arrow, MemTable},
+    parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder,
+};
+
+use super::{csv, json, text};
+use crate::cli::{sniff_compression, sniff_format, FileFormat, ReadOpts, TextOpts};
+
+const SNIFF_BYTES: usize = 4096;
+
+// stdin can only be consumed once and isn't seekable, so it is buffered into memory and
+// registered as a memory table
+pub fn read_stdin(
+    format: Option<FileFormat>,
+    read: &ReadOpts,
+    text: &TextOpts,
+) -> anyhow::Result<MemTable> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        return Err(anyhow!("Stdin is a terminal, pipe the dataset into taotie"));
+    }
+    let mut data = vec![];
+    stdin.lock().read_to_end(&mut data)?;
+    if data.is_empty() {
+        return Err(anyhow!("No data on stdin"));
+    }
+
+    let compression = sniff_compression(&data);
+    if compression.is_compressed() {
+        let mut decompressed = vec![];
+        compression
+            .convert_read(Cursor::new(data))?
+            .read_to_end(&mut decompressed)?;
+        data = decompressed;
+    }
+
+    let format = format
+        .or_else(|| sniff_format(&data[..data.len().min(SNIFF_BYTES)]))
+        .ok_or_else(|| anyhow!("Unknown format of stdin, use --format to specify it"))?;
+    match format {
+        FileFormat::Csv => csv::read_csv_bytes(&data, read),
+        FileFormat::Json => json::read_json_bytes(&data, read),
+        FileFormat::Text => text::read_text_bytes(&data, text, read),
+        FileFormat::Parquet => {
+            let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(data))?.build()?;
+            let schema = reader.schema();
+            let batches = reader.collect::<Result<Vec<_>, _>>()?;
+            Ok(MemTable::try_new(schema, vec![batches])?)
+        }
+        FileFormat::Arrow if data.starts_with(b"ARROW1") => {
+            let reader = FileReader::try_new(Cursor::new(data), None)?;
+            let schema = reader.schema();
+            let batches = reader.collect::<Result<Vec<_>, _>>()?;
+            Ok(MemTable::try_new(schema, vec![batches])?)
+        }
+        FileFormat::Arrow => {
+            let reader = StreamReader::try_new(Cursor::new(data), None)?;
+            let schema = reader.schema();
+            let batches = reader.collect::<Result<Vec<_>, _>>()?;
+            Ok(MemTable::try_new(schema, vec![batches])?)
+        }
+        FileFormat::Avro => {
+            let reader = avro_to_arrow::ReaderBuilder::new()
+                .read_schema()
+                .build(Cursor::new(data))?;
+            let schema = reader.schema();
+            let batches = reader.collect::<Result<Vec<_>, _>>()?;
+            Ok(MemTable::try_new(schema, vec![batches])?)
+        }
+        FileFormat::Excel | FileFormat::Sqlite => {
+            Err(anyhow!("Reading {:?} from stdin is not supported", format))
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::{collections::HashMap, env, fs, io::Read, path::PathBuf, sync::Arc};
+
+use anyhow::anyhow;
+use bytes::{Buf, Bytes};
+use datafusion::{
+    datasource::listing::ListingTableUrl, execution::context::SessionState, prelude::SessionContext,
+};
+use object_store::{
+    aws::AmazonS3Builder, azure::MicrosoftAzureBuilder, gcp::GoogleCloudStorageBuilder,
+    http::HttpBuilder, ClientOptions, ObjectStore,
+};
+use url::{Position, Url};
+
+use crate::cli::FileOpts;
+
+use super::listing;
+
+// reads a whole file, local or remote, for the formats that are loaded into memory
+pub async fn read_file(state: &SessionState, file_opts: &FileOpts) -> anyhow::Result<Vec<u8>> {
+    let (store, url) = single_file(state, &file_opts.filename)?;
+    let bytes = store.get(url.prefix()).await?.bytes().await?;
+    let mut data = vec![];
+    file_opts
+        .compression
+        .convert_read(bytes.reader())?
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+// the first bytes of a file, local or remote, still compressed, to tell what it holds
+pub async fn read_head(state: &SessionState, path: &str, len: usize) -> anyhow::Result<Bytes> {
+    let (store, url) = single_file(state, path)?;
+    let size = store.head(url.prefix()).await?.size;
+    Ok(store.get_range(url.prefix(), 0..len.min(size)).await?)
+}
+
+fn single_file(
+    state: &SessionState,
+    path: &str,
+) -> anyhow::Result<(Arc<dyn ObjectStore>, ListingTableUrl)> {
+    let url = ListingTableUrl::parse(path)?;
+    if url.is_collection() || listing::split_url_glob(path).is_some() {
+        return Err(anyhow!(
+            "Only a single file can be loaded into memory: {}",
+            path
+        ));
+    }
+    Ok((state.runtime_env().object_store(&url)?, url))
+}
+
+pub fn is_remote(path: &str) -> bool {
+    Url::parse(path).is_ok_and(|url| {
+        matches!(
+            url.scheme(),
+            "s3" | "s3a" | "gs" | "gcs" | "az" | "azure" | "abfs" | "abfss" | "http" | "https"
+        )
+    })
+}
+
+// registers a store for the bucket (or http host) of the given url, so datafusion can resolve
+// the listing table url against it; credentials come from the environment
+pub fn register(
+    ctx: &SessionContext,
+    stores: &mut HashMap<Url, String>,
+    path: &str,
+    endpoint: Option<&str>,
+) -> anyhow::Result<()> {
+    let url = Url::parse(path)?;
+    if url.host_str().is_none() {
+        return Err(anyhow!("Missing bucket in url: {}", path));
+    }
+    // same key as datafusion's object store registry uses for lookups
+    let base = Url::parse(&url[..Position::AfterPort])?;
+
+    // every dataset of a bucket or host shares its store, so a store with another endpoint, or
+    // another query such as a token, would be used by the datasets connected before
+    let (config, what) = match url.scheme() {
+        "http" | "https" => (url.query(), "query string"),
+        _ => (endpoint, "endpoint"),
+    };
+    let config = config.unwrap_or_default().to_string();
+    if stores
+        .get(&base)
+        .is_some_and(|registered| *registered != config)
+    {
+        return Err(anyhow!(
+            "{} is already connected with another {}",
+            base,
+            what
+        ));
+    }
+
+    let store: Arc<dyn ObjectStore> = match url.scheme() {
+        "s3" | "s3a" => {
+            let mut builder = AmazonS3Builder::from_env().with_url(path);
+            if env::var("AWS_ACCESS_KEY_ID").is_err() {
+                builder = with_aws_profile(builder);
+            }
+            if let Some(endpoint) = endpoint {
+                builder = builder
+                    .with_endpoint(endpoint)
+                    .with_allow_http(endpoint.starts_with("http://"));
+            }
+            Arc::new(builder.build()?)
+        }
+        "gs" | "gcs" if endpoint.is_some() => {
+            return Err(anyhow!("Endpoint is not supported for gcs: {}", path));
+        }
+        "gs" | "gcs" => Arc::new(
+            GoogleCloudStorageBuilder::from_env()
+                .with_url(path)
+                .build()?,
+        ),
+        "az" | "azure" | "abfs" | "abfss" => {
+            let mut builder = MicrosoftAzureBuilder::from_env().with_url(path);
+            if let Some(endpoint) = endpoint {
+                builder = builder
+                    .with_endpoint(endpoint.to_string())
+                    .with_allow_http(endpoint.starts_with("http://"));
+            }
+            Arc::new(builder.build()?)
+        }
+        // parquet footers and row groups are fetched with range requests; the query, e.g. a
+        // token, is sent with every request to the host
+        "http" | "https" => Arc::new(
+            HttpBuilder::new()
+                .with_url(match url.query() {
+                    Some(query) => format!("{}?{}", base, query),
+                    None => base.to_string(),
+                })
+                .with_client_options(ClientOptions::new().with_allow_http(url.scheme() == "http"))
+                .build()?,
+        ),
+        scheme => return Err(anyhow!("Unsupported object store: {}", scheme)),
+    };
+
+    ctx.register_object_store(&base, store);
+    stores.insert(base, config);
+    Ok(())
+}
+
+// object_store only reads credentials from env vars, so fall back to the shared aws files
+// for the profile named by `AWS_PROFILE`
+fn with_aws_profile(mut builder: AmazonS3Builder) -> AmazonS3Builder {
+    let Some(aws_dir) = dirs::home_dir().map(|home| home.join(".aws")) else {
+        return builder;
+    };
+    let profile = env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string());
+
+    let credentials = env::var("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| aws_dir.join("credentials"));
+    if let Some(section) = read_ini_section(&credentials, &profile) {
+        if let Some(key) = section.get("aws_access_key_id") {
+            builder = builder.with_access_key_id(key);
+        }
+        if let Some(secret) = section.get("aws_secret_access_key") {
+            builder = builder.with_secret_access_key(secret);
+        }
+        if let Some(token) = section.get("aws_session_token") {
+            builder = builder.with_token(token);
+        }
+    }
+
+    if env::var("AWS_REGION").is_err() && env::var("AWS_DEFAULT_REGION").is_err() {
+        let config = env::var("AWS_CONFIG_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| aws_dir.join("config"));
+        let section = match profile.as_str() {
+            "default" => "default".to_string(),
+            profile => format!("profile {}", profile),
+        };
+        if let Some(region) =
+            read_ini_section(&config, &section).and_then(|s| s.get("region").cloned())
+        {
+            builder = builder.with_region(region);
+        }
+    }
+    builder
+}
+
+fn read_ini_section(path: &PathBuf, name: &str) -> Option<HashMap<String, String>> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut section = None;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            if section.is_some() {
+                break;
+            }
+            if line[1..line.len() - 1].trim() == name {
+                section = Some(HashMap::new());
+            }
+        } else if let (Some(values), Some((key, value))) = (section.as_mut(), line.split_once('='))
+        {
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    section
+}
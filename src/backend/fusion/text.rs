@@ -0,0 +1,372 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use arrow::{
+    array::{ArrayRef, RecordBatch, StringArray, TimestampMicrosecondArray},
+    compute::{cast_with_options, CastOptions},
+    datatypes::{DataType, Field, Schema, TimeUnit},
+};
+use chrono::{DateTime, NaiveDateTime};
+use datafusion::{datasource::MemTable, execution::context::SessionState};
+use regex::Regex;
+
+use super::store;
+use crate::cli::{FileOpts, LogFormat, ReadOpts, TextOpts};
+
+const COMMON: &str = r#"^(?P<host>\S+) (?P<ident>\S+) (?P<remote_user>\S+) \[(?P<time>[^\]]+)\] "(?:(?P<method>[A-Z]+) (?P<path>\S+)(?: (?P<protocol>[^"]*))?|[^"]*)" (?P<status>\d{3}) (?P<size>\S+)"#;
+const COMBINED: &str = r#" "(?P<referer>[^"]*)" "(?P<user_agent>[^"]*)""#;
+
+// types tried in order on every value of a column, falling back to strings
+const INFER_TYPES: [DataType; 3] = [DataType::Int64, DataType::Float64, DataType::Date32];
+
+pub async fn read_text(
+    state: &SessionState,
+    file_opts: &FileOpts,
+    text: &TextOpts,
+    read: &ReadOpts,
+) -> anyhow::Result<MemTable> {
+    let data = store::read_file(state, file_opts).await?;
+    read_text_bytes(&data, text, read)
+}
+
+pub fn read_text_bytes(data: &[u8], text: &TextOpts, read: &ReadOpts) -> anyhow::Result<MemTable> {
+    let batch = parse_text(data, text, read)?;
+    Ok(MemTable::try_new(batch.schema(), vec![vec![batch]])?)
+}
+
+fn parse_text(data: &[u8], text: &TextOpts, read: &ReadOpts) -> anyhow::Result<RecordBatch> {
+    let data = String::from_utf8_lossy(data);
+    let lines = data.lines().filter(|line| !line.trim().is_empty());
+
+    // access logs write `-` for a missing value
+    let mut nulls = vec![""];
+    nulls.extend(read.null_value.as_deref());
+    let (names, rows) = match (&text.regex, text.log_format) {
+        (Some(regex), _) => parse_regex(regex, lines),
+        (None, Some(log_format)) => {
+            nulls.push("-");
+            parse_regex(&Regex::new(&log_regex(log_format))?, lines)
+        }
+        (None, None) if !text.widths.is_empty() => {
+            let names = text
+                .widths
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    column
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("column_{}", i + 1))
+                })
+                .collect::<Vec<_>>();
+            let rows = lines
+                .map(|line| split_widths(line, text.widths.iter().map(|column| column.width)))
+                .collect::<Vec<_>>();
+            (names, rows)
+        }
+        (None, None) => {
+            return Err(anyhow!(
+                "Text needs --widths, --regex or --log-format to parse its lines"
+            ))
+        }
+    };
+    if rows.is_empty() {
+        return Err(anyhow!("No line matched the text format"));
+    }
+
+    let mut fields = vec![];
+    let mut columns = vec![];
+    for (i, name) in names.iter().enumerate() {
+        let values = rows
+            .iter()
+            .map(|row| row[i].filter(|value| !nulls.contains(value)))
+            .collect::<Vec<_>>();
+        // the schema may only give the types of some columns
+        let field = read
+            .schema
+            .as_ref()
+            .and_then(|schema| schema.field_with_name(name).ok());
+        let (data_type, column) = match field {
+            Some(field) => {
+                let data_type = field.data_type().clone();
+                let column = build_column(&values, &data_type)?;
+                (data_type, column)
+            }
+            None => infer_column(&values),
+        };
+        fields.push(Field::new(name, data_type, true));
+        columns.push(column);
+    }
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+fn log_regex(log_format: LogFormat) -> String {
+    match log_format {
+        LogFormat::Common => COMMON.to_string(),
+        LogFormat::Combined => format!("{}{}", COMMON, COMBINED),
+        LogFormat::VhostCombined => format!(
+            r"^(?P<vhost>\S+) {}{}",
+            COMMON.trim_start_matches('^'),
+            COMBINED
+        ),
+    }
+}
+
+// lines that don't match are skipped, unnamed groups are ignored
+fn parse_regex<'a>(
+    regex: &Regex,
+    lines: impl Iterator<Item = &'a str>,
+) -> (Vec<String>, Vec<Vec<Option<&'a str>>>) {
+    let names = regex
+        .capture_names()
+        .flatten()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    let rows = lines
+        .filter_map(|line| regex.captures(line))
+        .map(|captures| {
+            names
+                .iter()
+                .map(|name| captures.name(name).map(|value| value.as_str()))
+                .collect()
+        })
+        .collect();
+    (names, rows)
+}
+
+// widths count characters, a missing width takes the rest of the line
+fn split_widths(line: &str, widths: impl Iterator<Item = Option<usize>>) -> Vec<Option<&str>> {
+    let mut rest = line;
+    widths
+        .map(|width| {
+            let end = match width {
+                Some(width) => rest
+                    .char_indices()
+                    .nth(width)
+                    .map_or(rest.len(), |(i, _)| i),
+                None => rest.len(),
+            };
+            let (value, tail) = rest.split_at(end);
+            rest = tail;
+            Some(value.trim())
+        })
+        .collect()
+}
+
+fn infer_column(values: &[Option<&str>]) -> (DataType, ArrayRef) {
+    let mut types = vec![];
+    // timestamps are tried first, as a date cast accepts them too, and keep their zone only
+    // when every value has one
+    let zoned = values
+        .iter()
+        .flatten()
+        .map(|value| parse_timestamp(value).map(|(_, zoned)| zoned))
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+    if !zoned.is_empty() && zoned.iter().all(|zoned| *zoned) {
+        types.push(DataType::Timestamp(
+            TimeUnit::Microsecond,
+            Some("UTC".into()),
+        ));
+    } else if !zoned.is_empty() && zoned.iter().all(|zoned| !zoned) {
+        types.push(DataType::Timestamp(TimeUnit::Microsecond, None));
+    }
+    if values.iter().any(Option::is_some) {
+        types.extend(INFER_TYPES);
+    }
+    for data_type in types {
+        if let Ok(column) = build_column(values, &data_type) {
+            return (data_type, column);
+        }
+    }
+    (DataType::Utf8, Arc::new(StringArray::from(values.to_vec())))
+}
+
+// values that don't parse as the type are an error rather than null
+fn build_column(values: &[Option<&str>], data_type: &DataType) -> anyhow::Result<ArrayRef> {
+    let strings = StringArray::from(values.to_vec());
+    let options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    match data_type {
+        DataType::Timestamp(_, tz) => {
+            let micros = values
+                .iter()
+                .map(|value| {
+                    value
+                        .map(|value| {
+                            parse_timestamp(value)
+                                .map(|(micros, _)| micros)
+                                .ok_or_else(|| anyhow!("Invalid timestamp: {}", value))
+                        })
+                        .transpose()
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let array = TimestampMicrosecondArray::from(micros).with_timezone_opt(tz.clone());
+            Ok(cast_with_options(&array, data_type, &options)?)
+        }
+        data_type => Ok(cast_with_options(&strings, data_type, &options)?),
+    }
+}
+
+// microseconds since the epoch, and whether the value carried a time zone; access logs write
+// times as `10/Oct/2000:13:55:36 -0700`
+fn parse_timestamp(value: &str) -> Option<(i64, bool)> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some((time.timestamp_micros(), true));
+    }
+    if let Ok(time) = DateTime::parse_from_str(value, "%d/%b/%Y:%H:%M:%S %z") {
+        return Some((time.timestamp_micros(), true));
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|time| (time.and_utc().timestamp_micros(), false))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{array::AsArray, datatypes::TimestampMicrosecondType};
+    use clap::Parser;
+
+    use super::*;
+
+    const COMMON_LINES: &str = r#"127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /apache_pb.gif HTTP/1.0" 200 2326
+10.0.0.2 - - [10/Oct/2000:13:56:01 -0700] "POST /login HTTP/1.1" 302 -
+not a log line
+"#;
+
+    #[derive(Parser)]
+    struct Opts {
+        #[command(flatten)]
+        text: TextOpts,
+        #[command(flatten)]
+        read: ReadOpts,
+    }
+
+    fn parse(data: &str, args: &[&str]) -> anyhow::Result<RecordBatch> {
+        let opts = Opts::parse_from(std::iter::once("text").chain(args.iter().copied()));
+        parse_text(data.as_bytes(), &opts.text, &opts.read)
+    }
+
+    fn strings(batch: &RecordBatch, name: &str) -> Vec<Option<String>> {
+        let column = cast_with_options(
+            batch.column_by_name(name).unwrap(),
+            &DataType::Utf8,
+            &CastOptions::default(),
+        )
+        .unwrap();
+        column
+            .as_string::<i32>()
+            .iter()
+            .map(|value| value.map(str::to_string))
+            .collect()
+    }
+
+    fn data_type<'a>(batch: &'a RecordBatch, name: &str) -> &'a DataType {
+        batch
+            .schema_ref()
+            .field_with_name(name)
+            .unwrap()
+            .data_type()
+    }
+
+    #[test]
+    fn split_fixed_widths() {
+        let batch = parse(
+            "2024-01-01INFO    started\n2024-01-02WARN    disk  low \n",
+            &["--widths", "date:10,level:8,msg:*"],
+        )
+        .unwrap();
+        assert_eq!(data_type(&batch, "date"), &DataType::Date32);
+        assert_eq!(
+            strings(&batch, "level"),
+            [Some("INFO".into()), Some("WARN".into())]
+        );
+        assert_eq!(
+            strings(&batch, "msg"),
+            [Some("started".into()), Some("disk  low".into())]
+        );
+
+        // unnamed columns are numbered, a short line leaves the last ones empty
+        let batch = parse("abc12\nde\n", &["--widths", "3,*"]).unwrap();
+        assert_eq!(
+            strings(&batch, "column_1"),
+            [Some("abc".into()), Some("de".into())]
+        );
+        assert_eq!(data_type(&batch, "column_2"), &DataType::Int64);
+        assert_eq!(strings(&batch, "column_2"), [Some("12".into()), None]);
+    }
+
+    #[test]
+    fn regex_groups_become_columns() {
+        let batch = parse(
+            "a-1 x\nb-2 -\nno match\n",
+            &["--regex", r"^(\w)-(?P<n>\d+) (?P<tag>\S+)$"],
+        )
+        .unwrap();
+        let names = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["n", "tag"]);
+        assert_eq!(data_type(&batch, "n"), &DataType::Int64);
+        // only logs read `-` as null
+        assert_eq!(strings(&batch, "tag"), [Some("x".into()), Some("-".into())]);
+    }
+
+    #[test]
+    fn common_log() {
+        let batch = parse(COMMON_LINES, &["--log-format", "common"]).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(
+            strings(&batch, "host"),
+            [Some("127.0.0.1".into()), Some("10.0.0.2".into())]
+        );
+        assert_eq!(strings(&batch, "ident"), [None, None]);
+        assert_eq!(strings(&batch, "remote_user"), [Some("frank".into()), None]);
+        assert_eq!(
+            strings(&batch, "method"),
+            [Some("GET".into()), Some("POST".into())]
+        );
+        assert_eq!(data_type(&batch, "status"), &DataType::Int64);
+        assert_eq!(strings(&batch, "size"), [Some("2326".into()), None]);
+        let time = batch.column_by_name("time").unwrap();
+        assert_eq!(
+            time.data_type(),
+            &DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+        );
+        assert_eq!(
+            time.as_primitive::<TimestampMicrosecondType>().value(0),
+            971_211_336_000_000
+        );
+    }
+
+    #[test]
+    fn combined_logs() {
+        let line = r#"127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /a.gif HTTP/1.0" 200 2326 "http://example.com/start.html" "Mozilla/4.08""#;
+        let batch = parse(line, &["--log-format", "combined"]).unwrap();
+        assert_eq!(
+            strings(&batch, "referer"),
+            [Some("http://example.com/start.html".into())]
+        );
+        assert_eq!(strings(&batch, "user_agent"), [Some("Mozilla/4.08".into())]);
+
+        let batch = parse(
+            &format!("example.com:80 {}", line),
+            &["--log-format", "vhost-combined"],
+        )
+        .unwrap();
+        assert_eq!(strings(&batch, "vhost"), [Some("example.com:80".into())]);
+        assert_eq!(strings(&batch, "path"), [Some("/a.gif".into())]);
+
+        // a common log line lacks the fields combined needs
+        assert!(parse(COMMON_LINES, &["--log-format", "combined"]).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::anyhow;
+
+use super::store;
+
+// the latest modification time and the number of files under a path, so both changed and
+// removed files are noticed
+type Fingerprint = Option<(SystemTime, usize)>;
+
+#[derive(Debug)]
+pub struct Watch {
+    path: PathBuf,
+    loaded: Fingerprint,
+    seen: Fingerprint,
+}
+
+impl Watch {
+    // globs are watched through the directory before the first wildcard
+    pub fn try_new(path: &str) -> anyhow::Result<Self> {
+        if store::is_remote(path) {
+            return Err(anyhow!("Watch is only supported for local files: {}", path));
+        }
+        let path = match path.find(['*', '?', '[']) {
+            Some(pos) => &path[..path[..pos].rfind('/').map_or(0, |i| i + 1)],
+            None => path,
+        };
+        let path = PathBuf::from(if path.is_empty() { "." } else { path });
+        let fingerprint = fingerprint(&path);
+        Ok(Self {
+            path,
+            loaded: fingerprint,
+            seen: fingerprint,
+        })
+    }
+
+    // a change is only reported once it stayed the same for a whole poll, so files still being
+    // written are not read half way
+    pub fn poll(&mut self) -> bool {
+        let current = fingerprint(&self.path);
+        let settled = current == self.seen;
+        self.seen = current;
+        if settled && current != self.loaded {
+            self.loaded = current;
+            return true;
+        }
+        false
+    }
+}
+
+fn fingerprint(path: &Path) -> Fingerprint {
+    walk(path, &mut HashSet::new())
+}
+
+// symlinks are followed like listing does, each directory once so a symlink cycle ends
+fn walk(path: &Path, visited: &mut HashSet<PathBuf>) -> Fingerprint {
+    let meta = fs::metadata(path).ok()?;
+    if !meta.is_dir() {
+        return Some((meta.modified().ok()?, 1));
+    }
+    if !visited.insert(fs::canonicalize(path).ok()?) {
+        return None;
+    }
+    let mut latest = meta.modified().ok()?;
+    let mut count = 0;
+    for entry in fs::read_dir(path).ok()?.filter_map(|entry| entry.ok()) {
+        if let Some((modified, files)) = walk(&entry.path(), visited) {
+            latest = latest.max(modified);
+            count += files;
+        }
+    }
+    Some((latest, count))
+}
@@ -1,28 +1,224 @@
-use clap::{ArgMatches, Parser};
-use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+use std::{fs, path::Path};
+
+use arrow::datatypes::SchemaRef;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use clap::{
+    parser::ValueSource, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, ValueEnum,
+};
+use datafusion::datasource::file_format::{
+    file_compression_type::FileCompressionType, DEFAULT_SCHEMA_INFER_MAX_RECORD,
+};
+use regex::Regex;
 
 use crate::{Backend, CmdExecutor, ReplContext, ReplMsg};
 
-use super::ReplResult;
+use super::{data_type::parse_schema, sniff, ReplResult};
 
-#[derive(Debug, Parser)]
+#[derive(Debug, Clone, Parser)]
 pub struct ConnectOpts {
-    #[arg(value_parser = verify_conn_str, help = "Connection String to the dataset, could be postgres or local file (support: csv, parquet, json)")]
+    #[arg(value_parser = verify_conn_str, help = "Connection String to the dataset, could be postgres, mysql, sqlite, local, http(s) or object store (s3, gs, az) file, directory or glob, delta table, iceberg table (metadata json, table directory, or iceberg:// warehouse), or - for stdin (support: csv, tsv, parquet, json, arrow, avro, xlsx, ods, log)")]
     pub conn: DataSetConn,
 
-    #[arg(short, long, help = "If database, the name of the table")]
+    #[arg(
+        short,
+        long,
+        help = "If database or iceberg warehouse, the name of the table (namespace.table for iceberg). Omit to register every table under a schema named after the dataset"
+    )]
     pub table: Option<String>,
 
+    #[arg(long, help = "If database and no table given, also register views")]
+    pub views: bool,
+
+    #[arg(
+        long,
+        help = "If excel workbook, the name of the sheet. Omit to register every sheet as <name>_<sheet>"
+    )]
+    pub sheet: Option<String>,
+
+    #[arg(
+        long,
+        help = "If object store url (s3, gs, az), override the endpoint, e.g. for a local minio server"
+    )]
+    pub endpoint: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "The format of the file, detected from its extension or content if omitted"
+    )]
+    pub format: Option<FileFormat>,
+
+    #[arg(
+        long,
+        conflicts_with = "timestamp",
+        help = "If delta table, the version to read"
+    )]
+    pub version: Option<i64>,
+
+    #[arg(
+        long,
+        value_parser = parse_timestamp,
+        help = "If delta or iceberg table, read the latest version committed at or before this time, e.g. 2024-05-01 or 2024-05-01T12:00:00Z"
+    )]
+    pub timestamp: Option<DateTime<Utc>>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["version", "timestamp"],
+        help = "If iceberg table, the id of the snapshot to read"
+    )]
+    pub snapshot_id: Option<i64>,
+
+    #[command(flatten)]
+    pub read: ReadOpts,
+
+    #[command(flatten)]
+    pub text: TextOpts,
+
     #[arg(short, long, help = "The name of the dataset")]
     pub name: String,
+
+    #[arg(
+        long,
+        help = "Replace the dataset if one with the same name is connected"
+    )]
+    pub replace: bool,
+
+    #[arg(
+        long,
+        help = "If local file or directory, reload the dataset whenever it changes on disk"
+    )]
+    pub watch: bool,
+
+    // the arguments as typed, saved to the catalog to connect the dataset in later sessions
+    #[arg(skip)]
+    pub args: Vec<String>,
+}
+
+// how files are read: the csv dialect, json records, null marker and schema
+#[derive(Debug, Clone, Args)]
+pub struct ReadOpts {
+    #[arg(long, value_parser = parse_byte, help = "If csv, the field delimiter, e.g. ';' or 'tab' (default: ',', or tab for tsv)")]
+    pub delimiter: Option<u8>,
+
+    #[arg(long, help = "If csv, the first line is data rather than column names")]
+    pub no_header: bool,
+
+    #[arg(long, value_parser = parse_byte, default_value = "\"", help = "If csv, the quote character")]
+    pub quote: u8,
+
+    #[arg(long, value_parser = parse_byte, help = "If csv, the escape character inside quoted fields")]
+    pub escape: Option<u8>,
+
+    #[arg(long, value_parser = parse_byte, help = "If csv, skip lines starting with this character")]
+    pub comment: Option<u8>,
+
+    #[arg(
+        long,
+        help = "If csv or text, the value read as null, e.g. 'NA' (csv columns holding it stay strings unless --schema types them)"
+    )]
+    pub null_value: Option<String>,
+
+    #[arg(
+        long,
+        help = "If csv or json, the number of rows used to infer the schema"
+    )]
+    pub infer_rows: Option<usize>,
+
+    #[arg(
+        long,
+        value_parser = parse_schema,
+        help = "If csv, json or text, the schema to use instead of inference, e.g. \"id:int64,ts:timestamp[ms,UTC],tags:list<utf8>\" or a json file of [{\"name\", \"type\", \"nullable\"}]"
+    )]
+    pub schema: Option<SchemaRef>,
+
+    #[arg(
+        long,
+        help = "If json, the path to the records inside the document, e.g. $.data.items (loads the file into memory)"
+    )]
+    pub json_path: Option<String>,
+
+    #[arg(
+        long,
+        help = "If json, flatten nested objects into parent.child columns (loads the file into memory)"
+    )]
+    pub flatten: bool,
+}
+
+// line oriented text such as logs, each line parsed into a row by one of these
+#[derive(Debug, Clone, Args)]
+#[group(multiple = false)]
+pub struct TextOpts {
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = parse_width,
+        help = "If text, split each line into columns of these widths, e.g. 10,8,* or date:10,level:8,msg:* (* takes the rest of the line)"
+    )]
+    pub widths: Vec<ColumnWidth>,
+
+    #[arg(
+        long,
+        value_parser = parse_regex,
+        help = "If text, parse each line with a regex whose named groups become columns, e.g. '^(?P<level>\\w+): (?P<msg>.*)$'"
+    )]
+    pub regex: Option<Regex>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "If text, parse each line as a web server access log"
+    )]
+    pub log_format: Option<LogFormat>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnWidth {
+    pub name: Option<String>,
+    pub width: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    // apache common log format, `%h %l %u %t "%r" %>s %b`
+    Common,
+    // common plus referer and user agent, also nginx's default
+    #[value(alias = "nginx")]
+    Combined,
+    // combined prefixed with the virtual host and port
+    VhostCombined,
 }
 
 #[derive(Debug, Clone)]
 pub enum DataSetConn {
     Postgres(String),
+    MySql(String),
+    Sqlite(String),
     Csv(FileOpts),
-    Parquet(String),
+    Parquet(FileOpts),
     NdJson(FileOpts),
+    Arrow(FileOpts),
+    Avro(FileOpts),
+    Text(FileOpts),
+    Excel(String),
+    Delta(String),
+    Iceberg(String),
+    Stdin(Option<FileFormat>),
+    Unknown(FileOpts),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FileFormat {
+    Csv,
+    #[value(alias = "ndjson", alias = "jsonl")]
+    Json,
+    Parquet,
+    Arrow,
+    Avro,
+    Excel,
+    Sqlite,
+    #[value(alias = "log")]
+    Text,
 }
 
 #[derive(Debug, Clone)]
@@ -34,45 +230,164 @@ pub struct FileOpts {
 
 fn verify_conn_str(s: &str) -> Result<DataSetConn, String> {
     let conn_str = s.to_string();
-    if conn_str.starts_with("postgres://") {
+    if conn_str.starts_with("postgres://") || conn_str.starts_with("postgresql://") {
         return Ok(DataSetConn::Postgres(conn_str));
     }
+    if conn_str.starts_with("mysql://") {
+        return Ok(DataSetConn::MySql(conn_str));
+    }
+    if let Some(path) = conn_str.strip_prefix("sqlite://") {
+        return Ok(DataSetConn::Sqlite(path.to_string()));
+    }
+    if let Some(path) = conn_str.strip_prefix("delta://") {
+        return Ok(DataSetConn::Delta(path.to_string()));
+    }
+    if let Some(path) = conn_str.strip_prefix("iceberg://") {
+        return Ok(DataSetConn::Iceberg(path.to_string()));
+    }
+    if s.ends_with(".metadata.json") {
+        return Ok(DataSetConn::Iceberg(conn_str));
+    }
+    if s == "-" || s == "stdin://" {
+        return Ok(DataSetConn::Stdin(None));
+    }
+
+    // local paths are only looked at when connecting, see `DataSetConn::detect`
+    if !s.contains("://") {
+        return Ok(DataSetConn::Unknown(FileOpts {
+            filename: s.to_string(),
+            ext: String::new(),
+            compression: FileCompressionType::UNCOMPRESSED,
+        }));
+    }
+
+    // remote files can't be sniffed, so an unknown extension is left for `--format` to decide
+    verify_file(s).or_else(|_| {
+        let ext = file_name(s).rsplit('.').next().unwrap_or_default();
+        Ok(DataSetConn::Unknown(FileOpts {
+            filename: s.to_string(),
+            ext: String::new(),
+            compression: compression_type(ext).unwrap_or(FileCompressionType::UNCOMPRESSED),
+        }))
+    })
+}
+
+// a delta or iceberg table directory, another directory, a file sniffed for its format, or a glob
+fn verify_local_path(s: &str) -> Result<DataSetConn, String> {
+    let path = Path::new(s);
+    if path.join("_delta_log").is_dir() {
+        return Ok(DataSetConn::Delta(s.to_string()));
+    }
+    if is_iceberg_table(path) {
+        return Ok(DataSetConn::Iceberg(s.to_string()));
+    }
+    if path.is_dir() {
+        let dir = if s.ends_with('/') {
+            s.to_string()
+        } else {
+            format!("{}/", s)
+        };
+        // the format is taken from a file in the directory, see `sample_file`
+        return Ok(DataSetConn::Unknown(FileOpts {
+            filename: dir,
+            ext: String::new(),
+            compression: FileCompressionType::UNCOMPRESSED,
+        }));
+    }
+    if path.is_file() {
+        return verify_local_file(s);
+    }
+    verify_file(s)
+}
 
-    let exts = conn_str.split('.').rev().collect::<Vec<_>>();
+// magic bytes win over the extension; text content only decides between csv and json when the
+// extension doesn't tell
+fn verify_local_file(s: &str) -> Result<DataSetConn, String> {
+    let (compression, sniffed) =
+        sniff::sniff_file(s).map_err(|e| format!("Failed to read file {}: {}", s, e))?;
+    let file_name = s.rsplit('/').next().unwrap_or(s);
+    let opts = FileOpts {
+        filename: s.to_string(),
+        ext: file_name
+            .split_once('.')
+            .map(|(_, ext)| ext.to_string())
+            .unwrap_or_default(),
+        compression,
+    };
+    let format = match (verify_file(s).ok().and_then(|conn| conn.format()), sniffed) {
+        (
+            _,
+            Some(
+                format @ (FileFormat::Parquet
+                | FileFormat::Arrow
+                | FileFormat::Avro
+                | FileFormat::Sqlite),
+            ),
+        ) => format,
+        (Some(format), _) | (None, Some(format)) => format,
+        (None, None) => return Ok(DataSetConn::Unknown(opts)),
+    };
+    DataSetConn::from_format(format, opts)
+}
+
+fn compression_type(ext: &str) -> Option<FileCompressionType> {
+    match ext {
+        "gz" => Some(FileCompressionType::GZIP),
+        "bz2" => Some(FileCompressionType::BZIP2),
+        "xz" => Some(FileCompressionType::XZ),
+        "zst" | "zstd" => Some(FileCompressionType::ZSTD),
+        _ => None,
+    }
+}
+
+// the query and fragment of a http url, e.g. a token, aren't part of the file name; other urls
+// and paths may use `?` as a wildcard
+fn file_name(s: &str) -> &str {
+    let path = match s.starts_with("http://") || s.starts_with("https://") {
+        true => s.split(['?', '#']).next().unwrap_or(s),
+        false => s,
+    };
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+// globs are matched by datafusion's listing table, so `data/*.csv` is parsed like `x.csv`
+fn verify_file(s: &str) -> Result<DataSetConn, String> {
+    let file_name = file_name(s);
+    let exts = file_name.split('.').rev().collect::<Vec<_>>();
     let len = exts.len();
     let mut exts = exts.into_iter().take(len - 1);
     let ext1 = exts.next();
     let ext2 = exts.next();
-    match (ext1, ext2) {
-        (Some(ext1), Some(ext2)) => {
-            let compression = match ext1 {
-                "gz" => FileCompressionType::GZIP,
-                "bz2" => FileCompressionType::BZIP2,
-                "xz" => FileCompressionType::XZ,
-                "zxtd" => FileCompressionType::ZSTD,
-                v => return Err(format!("Invalid compression type: {}", v)),
-            };
+    match (ext1, ext2, ext1.and_then(compression_type)) {
+        (Some(ext1), Some(ext2), Some(compression)) => {
+            // the listing table filters files by extension, which includes the compression suffix
             let opts = FileOpts {
                 filename: s.to_string(),
-                ext: ext2.to_string(),
+                ext: format!("{}.{}", ext2, ext1),
                 compression,
             };
-            match ext1 {
-                "csv" => Ok(DataSetConn::Csv(opts)),
+            match ext2 {
+                "csv" | "tsv" => Ok(DataSetConn::Csv(opts)),
                 "json" | "jsonl" | "ndjson" => Ok(DataSetConn::NdJson(opts)),
+                "log" => Ok(DataSetConn::Text(opts)),
                 v => Err(format!("Invalid file extension: {}", v)),
             }
         }
-        (Some(ext1), None) => {
+        (Some(ext1), _, _) => {
             let opts = FileOpts {
                 filename: s.to_string(),
                 ext: ext1.to_string(),
                 compression: FileCompressionType::UNCOMPRESSED,
             };
             match ext1 {
-                "csv" => Ok(DataSetConn::Csv(opts)),
+                "csv" | "tsv" => Ok(DataSetConn::Csv(opts)),
                 "json" | "jsonl" | "ndjson" => Ok(DataSetConn::NdJson(opts)),
-                "parquet" => Ok(DataSetConn::Parquet(s.to_string())),
+                "parquet" => Ok(DataSetConn::Parquet(opts)),
+                "arrow" | "feather" | "ipc" => Ok(DataSetConn::Arrow(opts)),
+                "avro" => Ok(DataSetConn::Avro(opts)),
+                "log" => Ok(DataSetConn::Text(opts)),
+                "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => Ok(DataSetConn::Excel(s.to_string())),
+                "db" | "sqlite" | "sqlite3" => Ok(DataSetConn::Sqlite(s.to_string())),
                 v => Err(format!("Invalid file extension: {}", v)),
             }
         }
@@ -80,25 +395,337 @@ fn verify_conn_str(s: &str) -> Result<DataSetConn, String> {
     }
 }
 
+// times without a zone are taken as utc
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.to_utc());
+    }
+    if let Ok(time) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+        return Ok(time.and_utc());
+    }
+    match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        Ok(date) => Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()),
+        Err(_) => Err(format!("Invalid timestamp: {}", s)),
+    }
+}
+
+fn parse_width(s: &str) -> Result<ColumnWidth, String> {
+    let (name, width) = match s.split_once(':') {
+        Some((name, width)) => (Some(name.trim().to_string()), width.trim()),
+        None => (None, s.trim()),
+    };
+    let width = match width {
+        "*" => None,
+        width => match width.parse() {
+            Ok(0) | Err(_) => return Err(format!("Invalid column width: {}", s)),
+            Ok(width) => Some(width),
+        },
+    };
+    Ok(ColumnWidth { name, width })
+}
+
+fn parse_regex(s: &str) -> Result<Regex, String> {
+    let regex = Regex::new(s).map_err(|e| e.to_string())?;
+    if regex.capture_names().flatten().next().is_none() {
+        return Err(format!("Expect named capture groups in regex: {}", s));
+    }
+    Ok(regex)
+}
+
+fn parse_byte(s: &str) -> Result<u8, String> {
+    match s {
+        "tab" | "\\t" => Ok(b'\t'),
+        s if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        s => Err(format!("Expect a single ascii character: {}", s)),
+    }
+}
+
+fn is_iceberg_table(dir: &Path) -> bool {
+    fs::read_dir(dir.join("metadata")).is_ok_and(|entries| {
+        entries.filter_map(|entry| entry.ok()).any(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.ends_with(".metadata.json"))
+        })
+    })
+}
+
+// picks the first data file in the directory tree to decide the format of the whole directory,
+// skipping hidden and metadata files such as `_SUCCESS`
+fn find_sample_file(dir: &Path) -> Option<String> {
+    let mut entries = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| !name.starts_with('.') && !name.starts_with('_'))
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    for path in entries.iter().filter(|path| path.is_file()) {
+        let path = path.to_str()?;
+        if verify_file(path).is_ok_and(|conn| conn.is_listing()) {
+            return Some(path.to_string());
+        }
+    }
+    entries
+        .iter()
+        .filter(|path| path.is_dir())
+        .find_map(|path| find_sample_file(path))
+}
+
+impl ReadOpts {
+    pub fn delimiter(&self, file_opts: &FileOpts) -> u8 {
+        match self.delimiter {
+            Some(delimiter) => delimiter,
+            None if file_opts.ext.split('.').any(|ext| ext == "tsv") => b'\t',
+            None => b',',
+        }
+    }
+
+    pub fn schema_infer_rows(&self) -> usize {
+        self.infer_rows.unwrap_or(DEFAULT_SCHEMA_INFER_MAX_RECORD)
+    }
+}
+
+impl TextOpts {
+    pub fn is_set(&self) -> bool {
+        !self.widths.is_empty() || self.regex.is_some() || self.log_format.is_some()
+    }
+}
+
+impl DataSetConn {
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            DataSetConn::Csv(opts)
+            | DataSetConn::NdJson(opts)
+            | DataSetConn::Parquet(opts)
+            | DataSetConn::Arrow(opts)
+            | DataSetConn::Avro(opts)
+            | DataSetConn::Text(opts)
+            | DataSetConn::Unknown(opts) => Some(&opts.filename),
+            DataSetConn::Excel(path)
+            | DataSetConn::Sqlite(path)
+            | DataSetConn::Delta(path)
+            | DataSetConn::Iceberg(path) => Some(path),
+            DataSetConn::Postgres(_) | DataSetConn::MySql(_) | DataSetConn::Stdin(_) => None,
+        }
+    }
+
+    // local paths are told apart by what is on disk, which is only read when connecting
+    pub fn detect(self) -> Result<Self, String> {
+        match self {
+            DataSetConn::Unknown(opts) if !opts.filename.contains("://") => {
+                verify_local_path(&opts.filename)
+            }
+            conn => Ok(conn),
+        }
+    }
+
+    // `--format` overrides whatever was detected from the extension or content
+    pub fn with_format(self, format: FileFormat) -> Result<Self, String> {
+        match self {
+            DataSetConn::Csv(opts)
+            | DataSetConn::NdJson(opts)
+            | DataSetConn::Parquet(opts)
+            | DataSetConn::Arrow(opts)
+            | DataSetConn::Avro(opts)
+            | DataSetConn::Text(opts)
+            | DataSetConn::Unknown(opts) => Self::from_format(format, opts),
+            DataSetConn::Excel(path) | DataSetConn::Sqlite(path) => Self::from_format(
+                format,
+                FileOpts {
+                    filename: path,
+                    ext: String::new(),
+                    compression: FileCompressionType::UNCOMPRESSED,
+                },
+            ),
+            DataSetConn::Stdin(_) => Ok(DataSetConn::Stdin(Some(format))),
+            DataSetConn::Postgres(_)
+            | DataSetConn::MySql(_)
+            | DataSetConn::Delta(_)
+            | DataSetConn::Iceberg(_) => Err("Format is only supported for files".to_string()),
+        }
+    }
+
+    fn from_format(format: FileFormat, opts: FileOpts) -> Result<Self, String> {
+        match format {
+            FileFormat::Csv => Ok(DataSetConn::Csv(opts)),
+            FileFormat::Json => Ok(DataSetConn::NdJson(opts)),
+            FileFormat::Text => Ok(DataSetConn::Text(opts)),
+            _ if opts.compression.is_compressed() => Err(format!(
+                "Compression is only supported for csv, json and text: {}",
+                opts.filename
+            )),
+            FileFormat::Parquet => Ok(DataSetConn::Parquet(opts)),
+            FileFormat::Arrow => Ok(DataSetConn::Arrow(opts)),
+            FileFormat::Avro => Ok(DataSetConn::Avro(opts)),
+            FileFormat::Excel => Ok(DataSetConn::Excel(opts.filename)),
+            FileFormat::Sqlite => Ok(DataSetConn::Sqlite(opts.filename)),
+        }
+    }
+
+    fn format(&self) -> Option<FileFormat> {
+        match self {
+            DataSetConn::Csv(_) => Some(FileFormat::Csv),
+            DataSetConn::NdJson(_) => Some(FileFormat::Json),
+            DataSetConn::Parquet(_) => Some(FileFormat::Parquet),
+            DataSetConn::Arrow(_) => Some(FileFormat::Arrow),
+            DataSetConn::Avro(_) => Some(FileFormat::Avro),
+            DataSetConn::Text(_) => Some(FileFormat::Text),
+            DataSetConn::Excel(_) => Some(FileFormat::Excel),
+            DataSetConn::Sqlite(_) => Some(FileFormat::Sqlite),
+            DataSetConn::Stdin(format) => *format,
+            DataSetConn::Postgres(_)
+            | DataSetConn::MySql(_)
+            | DataSetConn::Delta(_)
+            | DataSetConn::Iceberg(_)
+            | DataSetConn::Unknown(_) => None,
+        }
+    }
+
+    // a local directory takes the format of the first data file found in it
+    pub fn sample_file(&self) -> Option<String> {
+        match self {
+            DataSetConn::Unknown(opts) if Path::new(&opts.filename).is_dir() => {
+                find_sample_file(Path::new(&opts.filename))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn with_sample_file(self, sample: &str) -> Result<Self, String> {
+        match self.path() {
+            Some(dir) => verify_file(sample)?.with_path(dir.to_string()),
+            None => Ok(self),
+        }
+    }
+
+    fn is_listing(&self) -> bool {
+        matches!(
+            self,
+            DataSetConn::Csv(_)
+                | DataSetConn::NdJson(_)
+                | DataSetConn::Parquet(_)
+                | DataSetConn::Arrow(_)
+                | DataSetConn::Avro(_)
+        )
+    }
+
+    fn with_path(self, path: String) -> Result<Self, String> {
+        match self {
+            DataSetConn::Csv(opts) => Ok(DataSetConn::Csv(FileOpts {
+                filename: path,
+                ..opts
+            })),
+            DataSetConn::NdJson(opts) => Ok(DataSetConn::NdJson(FileOpts {
+                filename: path,
+                ..opts
+            })),
+            DataSetConn::Arrow(opts) => Ok(DataSetConn::Arrow(FileOpts {
+                filename: path,
+                ..opts
+            })),
+            DataSetConn::Avro(opts) => Ok(DataSetConn::Avro(FileOpts {
+                filename: path,
+                ..opts
+            })),
+            DataSetConn::Parquet(opts) => Ok(DataSetConn::Parquet(FileOpts {
+                filename: path,
+                ..opts
+            })),
+            _ => Err(format!("Directory is not supported for: {}", path)),
+        }
+    }
+}
+
 pub fn connect(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
-    let conn = args
-        .get_one::<DataSetConn>("conn")
-        .expect("expect conn_str")
-        .to_owned();
-    let table = args.get_one::<String>("table").map(|s| s.to_string());
-    let name = args
-        .get_one::<String>("name")
-        .expect("expect name")
-        .to_string();
-
-    let (msg, rx) = ReplMsg::new(ConnectOpts::new(conn, table, name));
+    let mut opts = ConnectOpts::from_arg_matches(&args).expect("expect connect options");
+    opts.args = command_line(&args);
+    let (msg, rx) = ReplMsg::new(opts);
     Ok(ctx.send(msg, rx))
 }
 
-impl ConnectOpts {
-    pub fn new(conn: DataSetConn, table: Option<String>, name: String) -> Self {
-        Self { conn, table, name }
+// datasets given to taotie on its command line are named after their file, and aren't saved to
+// the catalog
+pub fn connect_file(ctx: &mut ReplContext, path: &str) -> Option<String> {
+    let name = dataset_name(path);
+    match ConnectOpts::try_parse_from(["connect", path, "--name", &name, "--replace"]) {
+        Ok(opts) => {
+            let (msg, rx) = ReplMsg::new(opts);
+            ctx.send(msg, rx)
+        }
+        Err(e) => {
+            eprintln!("{}", e.render().to_string().trim_end());
+            ctx.failures += 1;
+            None
+        }
+    }
+}
+
+// `data/sales.csv.gz` becomes `sales`
+fn dataset_name(path: &str) -> String {
+    if path == "-" {
+        return "stdin".to_string();
+    }
+    let file_name = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path);
+    let name = identifier(file_name.split('.').next().unwrap_or(file_name));
+    match name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        true => name,
+        false => format!("t_{}", name),
+    }
+}
+
+// file and sheet names are free text, so keep them usable as unquoted sql identifiers
+pub fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_lowercase(),
+            false => '_',
+        })
+        .collect()
+}
+
+// values are written as `--long=value`, so ones starting with `-` aren't read as options, and
+// local paths are made absolute, so a later session started elsewhere finds them
+fn command_line(matches: &ArgMatches) -> Vec<String> {
+    let mut args = vec![];
+    for arg in ConnectOpts::command().get_arguments() {
+        let id = arg.get_id().as_str();
+        if id == "replace" || matches.value_source(id) != Some(ValueSource::CommandLine) {
+            continue;
+        }
+        let values = matches
+            .get_raw(id)
+            .into_iter()
+            .flatten()
+            .map(|value| value.to_string_lossy().to_string())
+            .map(|value| match id {
+                "conn" if !value.contains("://") && value != "-" => absolute_path(value),
+                "schema" if Path::new(&value).is_file() => absolute_path(value),
+                _ => value,
+            });
+        match arg.get_long() {
+            None => args.extend(values),
+            Some(long) if !arg.get_action().takes_values() => args.push(format!("--{}", long)),
+            Some(long) => args.extend(values.map(|value| format!("--{}={}", long, value))),
+        }
     }
+    args
+}
+
+fn absolute_path(path: String) -> String {
+    std::path::absolute(&path)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or(path)
 }
 
 impl CmdExecutor for ConnectOpts {
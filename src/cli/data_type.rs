@@ -0,0 +1,228 @@
+use std::{fs, path::Path, sync::Arc};
+
+use arrow::datatypes::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct FieldSpec {
+    name: String,
+    #[serde(rename = "type")]
+    data_type: String,
+    #[serde(default = "default_nullable")]
+    nullable: bool,
+}
+
+fn default_nullable() -> bool {
+    true
+}
+
+// either an inline `name:type,...` list or a json file holding `[{"name", "type", "nullable"}]`
+pub fn parse_schema(s: &str) -> Result<SchemaRef, String> {
+    let fields = if s.ends_with(".json") {
+        if !Path::new(s).is_file() {
+            return Err(format!("Schema file not found: {}", s));
+        }
+        let content = fs::read_to_string(s).map_err(|e| e.to_string())?;
+        let specs: Vec<FieldSpec> = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid schema file {}: {}", s, e))?;
+        specs
+            .into_iter()
+            .map(|spec| {
+                let data_type = parse_data_type(&spec.data_type)?;
+                Ok(Field::new(spec.name, data_type, spec.nullable))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    } else {
+        parse_fields(s)?
+    };
+    if fields.is_empty() {
+        return Err(format!("Empty schema: {}", s));
+    }
+    Ok(Arc::new(Schema::new(fields)))
+}
+
+fn parse_fields(s: &str) -> Result<Vec<Field>, String> {
+    split_top_level(s)?
+        .into_iter()
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            let (name, data_type) = field
+                .split_once(':')
+                .ok_or_else(|| format!("Expect name:type for field: {}", field))?;
+            Ok(Field::new(name.trim(), parse_data_type(data_type)?, true))
+        })
+        .collect()
+}
+
+// type names follow arrow's, e.g. `int64`, `timestamp[ms,UTC]`, `decimal[10,2]`, `list<utf8>`
+// or `struct<a:int32,b:utf8>`
+pub fn parse_data_type(s: &str) -> Result<DataType, String> {
+    let s = s.trim();
+    let (name, args) = match s.find(['[', '<', '(']) {
+        Some(pos) => {
+            let close = closing(s[pos..].chars().next().unwrap_or_default());
+            if !s.ends_with(close) {
+                return Err(format!("Unbalanced brackets in type: {}", s));
+            }
+            (&s[..pos], split_top_level(&s[pos + 1..s.len() - 1])?)
+        }
+        None => (s, vec![]),
+    };
+    let name = name.trim().to_lowercase();
+
+    let data_type = match (name.as_str(), args.as_slice()) {
+        ("null", []) => DataType::Null,
+        ("bool" | "boolean", []) => DataType::Boolean,
+        ("int8", []) => DataType::Int8,
+        ("int16", []) => DataType::Int16,
+        ("int32" | "int", []) => DataType::Int32,
+        ("int64" | "bigint", []) => DataType::Int64,
+        ("uint8", []) => DataType::UInt8,
+        ("uint16", []) => DataType::UInt16,
+        ("uint32", []) => DataType::UInt32,
+        ("uint64", []) => DataType::UInt64,
+        ("float16", []) => DataType::Float16,
+        ("float32" | "float", []) => DataType::Float32,
+        ("float64" | "double", []) => DataType::Float64,
+        ("utf8" | "string", []) => DataType::Utf8,
+        ("large_utf8", []) => DataType::LargeUtf8,
+        ("binary", []) => DataType::Binary,
+        ("large_binary", []) => DataType::LargeBinary,
+        ("date32" | "date", []) => DataType::Date32,
+        ("date64", []) => DataType::Date64,
+        ("time32", [unit]) => DataType::Time32(parse_time_unit(unit)?),
+        ("time64", [unit]) => DataType::Time64(parse_time_unit(unit)?),
+        ("timestamp", []) => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ("timestamp", [unit]) => DataType::Timestamp(parse_time_unit(unit)?, None),
+        ("timestamp", [unit, tz]) => {
+            DataType::Timestamp(parse_time_unit(unit)?, Some(tz.trim().into()))
+        }
+        ("duration", [unit]) => DataType::Duration(parse_time_unit(unit)?),
+        ("decimal" | "decimal128", [precision, scale]) => {
+            DataType::Decimal128(parse_number(precision)?, parse_number(scale)?)
+        }
+        ("list", [item]) => DataType::new_list(parse_data_type(item)?, true),
+        ("large_list", [item]) => DataType::new_large_list(parse_data_type(item)?, true),
+        ("struct", _) => {
+            let fields = args
+                .iter()
+                .map(|field| parse_fields(field))
+                .collect::<Result<Vec<_>, _>>()?;
+            DataType::Struct(Fields::from(fields.concat()))
+        }
+        _ => return Err(format!("Invalid data type: {}", s)),
+    };
+    Ok(data_type)
+}
+
+fn parse_time_unit(s: &str) -> Result<TimeUnit, String> {
+    match s.trim().to_lowercase().as_str() {
+        "s" | "second" => Ok(TimeUnit::Second),
+        "ms" | "millisecond" => Ok(TimeUnit::Millisecond),
+        "us" | "microsecond" => Ok(TimeUnit::Microsecond),
+        "ns" | "nanosecond" => Ok(TimeUnit::Nanosecond),
+        v => Err(format!("Invalid time unit: {}", v)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.trim()
+        .parse()
+        .map_err(|_| format!("Invalid number: {}", s))
+}
+
+fn closing(open: char) -> char {
+    match open {
+        '[' => ']',
+        '<' => '>',
+        _ => ')',
+    }
+}
+
+// splits on commas that are not nested inside a type's brackets, each bracket closed by its kind
+fn split_top_level(s: &str) -> Result<Vec<&str>, String> {
+    let mut parts = vec![];
+    let mut open = vec![];
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' | '<' | '(' => open.push(closing(c)),
+            ']' | '>' | ')' if open.pop() != Some(c) => {
+                return Err(format!("Unbalanced brackets in type: {}", s))
+            }
+            ',' if open.is_empty() => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !open.is_empty() {
+        return Err(format!("Unbalanced brackets in type: {}", s));
+    }
+    parts.push(s[start..].trim());
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_nested_types() {
+        assert_eq!(parse_data_type("Int64"), Ok(DataType::Int64));
+        assert_eq!(
+            parse_data_type("timestamp[ms, UTC]"),
+            Ok(DataType::Timestamp(
+                TimeUnit::Millisecond,
+                Some("UTC".into())
+            ))
+        );
+        assert_eq!(
+            parse_data_type("decimal[10,2]"),
+            Ok(DataType::Decimal128(10, 2))
+        );
+        assert_eq!(
+            parse_data_type("list<struct<a:int32,b:list<utf8>>>"),
+            Ok(DataType::new_list(
+                DataType::Struct(Fields::from(vec![
+                    Field::new("a", DataType::Int32, true),
+                    Field::new("b", DataType::new_list(DataType::Utf8, true), true),
+                ])),
+                true
+            ))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_types() {
+        for s in [
+            "list<utf8]",
+            "struct<a:list<int32]>",
+            "decimal[10,2",
+            "list<utf8>>",
+            "int128",
+            "time32[hour]",
+            "decimal[a,2]",
+        ] {
+            assert!(parse_data_type(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_inline_schema() {
+        let schema = parse_schema("id:int64, ts:timestamp[ms,UTC], tags:list<utf8>").unwrap();
+        assert_eq!(schema.fields().len(), 3);
+        assert_eq!(schema.field(2).name(), "tags");
+        assert!(parse_schema("id").is_err());
+        assert!(parse_schema("").is_err());
+    }
+
+    #[test]
+    fn missing_schema_file() {
+        assert_eq!(
+            parse_schema("no_such_schema.json"),
+            Err("Schema file not found: no_such_schema.json".to_string())
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{Backend, CmdExecutor, ReplContext, ReplMsg};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct DisconnectOpts {
+    #[arg(help = "The name of the dataset")]
+    pub name: String,
+}
+
+pub fn disconnect(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("expect name")
+        .to_string();
+
+    let (msg, rx) = ReplMsg::new(DisconnectOpts::new(name));
+    Ok(ctx.send(msg, rx))
+}
+
+impl DisconnectOpts {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl CmdExecutor for DisconnectOpts {
+    async fn execute<T: Backend>(self, backend: &mut T) -> anyhow::Result<String> {
+        backend.disconnect(&self.name).await?;
+        Ok(format!("Disconnected dataset: {}", self.name))
+    }
+}
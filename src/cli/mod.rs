@@ -1,36 +1,75 @@
 mod connect;
+mod data_type;
 mod describe;
+mod disconnect;
+mod forget;
 mod head;
 mod list;
+mod query;
+mod refresh;
 mod schema;
+mod sniff;
+mod source;
 mod sql;
+mod workspace;
 pub use self::{
-    connect::{ConnectOpts, DataSetConn},
+    connect::{
+        connect_file, identifier, ConnectOpts, DataSetConn, FileFormat, FileOpts, LogFormat,
+        ReadOpts, TextOpts,
+    },
     describe::DescribeOpts,
+    disconnect::DisconnectOpts,
+    forget::ForgetOpts,
     head::HeadOpts,
     list::ListOpts,
+    query::QueryOpts,
+    refresh::RefreshOpts,
     schema::SchemaOpts,
+    sniff::{sniff_compression, sniff_format},
+    source::{source_file, SourceOpts},
     sql::SqlOpts,
+    workspace::WorkspaceOpts,
 };
 use anyhow::Result;
 use clap::Parser;
 use enum_dispatch::enum_dispatch;
 
 pub use self::{
-    connect::connect, describe::describe, head::head, list::list, schema::schema, sql::sql,
+    connect::connect, describe::describe, disconnect::disconnect, forget::forget, head::head,
+    list::list, query::query, refresh::refresh, schema::schema, source::source, sql::sql,
+    workspace::workspace,
 };
 
 type ReplResult = Result<Option<String>, reedline_repl_rs::Error>;
 
+// one command is built per input line, so the size of the connect options doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Parser)]
 #[enum_dispatch(CmdExecutor)]
 pub enum ReplCommand {
     //子命令，-- csv，中间有个空格
     #[command(
         name = "connect",
-        about = "Connect to a dataset and register it to taotie"
+        about = "Connect to a dataset and register it to taotie",
+        long_about = "Connect to a dataset and register it to taotie. The arguments are saved to catalog.json of the workspace, passwords of database urls included, so later sessions connect the dataset again; the file is only readable by you, use forget to remove a dataset from it"
     )]
     Connect(ConnectOpts),
+    #[command(
+        name = "disconnect",
+        visible_alias = "drop",
+        about = "Disconnect a dataset and free the memory it holds"
+    )]
+    Disconnect(DisconnectOpts),
+    #[command(
+        name = "refresh",
+        about = "Read the source of a dataset again, e.g. after its files changed"
+    )]
+    Refresh(RefreshOpts),
+    #[command(
+        name = "forget",
+        about = "Remove a dataset from the catalog, so later sessions don't connect it"
+    )]
+    Forget(ForgetOpts),
     #[command(name = "list", about = "List registered datasets")]
     List(ListOpts),
     #[command(name = "schema", about = "Describe the schema of the dataset")]
@@ -41,4 +80,10 @@ pub enum ReplCommand {
     Head(HeadOpts),
     #[command(about = "Query a dataset using given SQL")]
     Sql(SqlOpts),
+    #[command(about = "Run the commands of a script file, one per line")]
+    Source(SourceOpts),
+    #[command(about = "Create, switch or list workspaces, each with its own datasets and views")]
+    Workspace(WorkspaceOpts),
+    #[command(about = "Save, run or list the queries of the workspace")]
+    Query(QueryOpts),
 }
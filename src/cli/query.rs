@@ -0,0 +1,61 @@
+use clap::{ArgMatches, FromArgMatches, Parser, Subcommand};
+
+use crate::{Backend, CmdExecutor, ReplContext, ReplDisplay, ReplMsg};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct QueryOpts {
+    #[command(subcommand)]
+    pub action: QueryAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum QueryAction {
+    #[command(about = "Save a query to the workspace, replacing one with the same name")]
+    Save {
+        #[arg(help = "The name of the query")]
+        name: String,
+        #[arg(help = "The sql of the query")]
+        sql: String,
+    },
+    #[command(about = "Run a saved query")]
+    Run {
+        #[arg(help = "The name of the query")]
+        name: String,
+    },
+    #[command(about = "List the saved queries of the workspace")]
+    List,
+    #[command(about = "Delete a saved query")]
+    Delete {
+        #[arg(help = "The name of the query")]
+        name: String,
+    },
+}
+
+pub fn query(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let opts = QueryOpts::from_arg_matches(&args).expect("expect query options");
+    let (msg, rx) = ReplMsg::new(opts);
+    Ok(ctx.send(msg, rx))
+}
+
+impl CmdExecutor for QueryOpts {
+    async fn execute<T: Backend>(self, backend: &mut T) -> anyhow::Result<String> {
+        match self.action {
+            QueryAction::Save { name, sql } => {
+                backend.save_query(&name, &sql).await?;
+                Ok(format!("Saved query: {}", name))
+            }
+            QueryAction::Run { name } => {
+                let sql = backend.query_sql(&name)?;
+                let df = backend.sql(&sql).await?;
+                df.display().await
+            }
+            QueryAction::List => backend.list_queries().await?.display().await,
+            QueryAction::Delete { name } => {
+                backend.delete_query(&name).await?;
+                Ok(format!("Deleted query: {}", name))
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{Backend, CmdExecutor, ReplContext, ReplMsg};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct RefreshOpts {
+    #[arg(help = "The name of the dataset")]
+    pub name: String,
+}
+
+pub fn refresh(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("expect name")
+        .to_string();
+
+    let (msg, rx) = ReplMsg::new(RefreshOpts::new(name));
+    Ok(ctx.send(msg, rx))
+}
+
+impl RefreshOpts {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl CmdExecutor for RefreshOpts {
+    async fn execute<T: Backend>(self, backend: &mut T) -> anyhow::Result<String> {
+        backend.refresh(&self.name).await?;
+        Ok(format!("Refreshed dataset: {}", self.name))
+    }
+}
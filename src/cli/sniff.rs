@@ -0,0 +1,84 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+};
+
+use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+
+use super::FileFormat;
+
+const SNIFF_BYTES: u64 = 4096;
+
+// detects compression and format of a local file from its leading bytes; text that is not json
+// is assumed to be csv, and `None` means the content is not recognized
+pub fn sniff_file(path: &str) -> io::Result<(FileCompressionType, Option<FileFormat>)> {
+    let head = read_head(File::open(path)?)?;
+    let compression = sniff_compression(&head);
+    if !compression.is_compressed() {
+        return Ok((compression, sniff_format(&head)));
+    }
+
+    let reader = compression
+        .convert_read(File::open(path)?)
+        .map_err(io::Error::other)?;
+    let head = read_head(reader)?;
+    Ok((compression, sniff_format(&head)))
+}
+
+fn read_head(reader: impl Read) -> io::Result<Vec<u8>> {
+    let mut head = vec![];
+    reader.take(SNIFF_BYTES).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+pub fn sniff_compression(head: &[u8]) -> FileCompressionType {
+    if head.starts_with(&[0x1f, 0x8b]) {
+        FileCompressionType::GZIP
+    } else if head.starts_with(b"BZh") {
+        FileCompressionType::BZIP2
+    } else if head.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        FileCompressionType::XZ
+    } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        FileCompressionType::ZSTD
+    } else {
+        FileCompressionType::UNCOMPRESSED
+    }
+}
+
+pub fn sniff_format(head: &[u8]) -> Option<FileFormat> {
+    if head.starts_with(b"PAR1") {
+        return Some(FileFormat::Parquet);
+    }
+    // ipc file magic, or the continuation marker the ipc stream format starts with; feather v1 is
+    // recognized only to be refused with a clear error
+    if head.starts_with(b"ARROW1")
+        || head.starts_with(&[0xff, 0xff, 0xff, 0xff])
+        || head.starts_with(b"FEA1")
+    {
+        return Some(FileFormat::Arrow);
+    }
+    if head.starts_with(b"Obj\x01") {
+        return Some(FileFormat::Avro);
+    }
+    if head.starts_with(b"SQLite format 3\0") {
+        return Some(FileFormat::Sqlite);
+    }
+    sniff_text(head)
+}
+
+fn sniff_text(head: &[u8]) -> Option<FileFormat> {
+    // the head may end in the middle of a multi-byte character
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+    if text.contains('\0') {
+        return None;
+    }
+    match text.trim_start().chars().next() {
+        Some('{' | '[') => Some(FileFormat::Json),
+        Some(_) => Some(FileFormat::Csv),
+        None => None,
+    }
+}
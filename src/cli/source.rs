@@ -0,0 +1,97 @@
+use std::{fs, path::Path};
+
+use anyhow::anyhow;
+use clap::{ArgMatches, Parser};
+
+use crate::{repl::is_incomplete, run_line, Backend, CmdExecutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct SourceOpts {
+    #[arg(
+        help = "The script file, one taotie command per line, sql until `;`, # starts a comment line"
+    )]
+    pub path: String,
+}
+
+pub fn source(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let path = args.get_one::<String>("path").expect("expect path");
+    match source_file(ctx, Path::new(path)) {
+        Ok(summary) => Ok(Some(summary)),
+        Err(e) => {
+            eprintln!("Failed to source {}: {}", path, e);
+            ctx.failures += 1;
+            Ok(None)
+        }
+    }
+}
+
+// every line runs even after one failed, and each failed line is reported with its number
+pub fn source_file(ctx: &mut ReplContext, path: &Path) -> anyhow::Result<String> {
+    let content = fs::read_to_string(path)?;
+    let canonical = path.canonicalize()?;
+    if ctx.sourcing.contains(&canonical) {
+        return Err(anyhow!(
+            "Script is already being sourced: {}",
+            path.display()
+        ));
+    }
+    ctx.sourcing.push(canonical);
+
+    // sql may span lines, a statement is reported by the line it starts on
+    let mut statements: Vec<(usize, String)> = vec![];
+    let mut sql: Option<(usize, String)> = None;
+    for (i, line) in content.lines().enumerate() {
+        if let Some((start, mut statement)) = sql.take() {
+            statement.push('\n');
+            statement.push_str(line);
+            match is_incomplete(&statement) {
+                true => sql = Some((start, statement)),
+                false => statements.push((start, statement)),
+            }
+            continue;
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match is_incomplete(line) {
+            true => sql = Some((i, line.to_string())),
+            false => statements.push((i, line.to_string())),
+        }
+    }
+    statements.extend(sql);
+
+    let (ran, mut failed) = (statements.len(), 0);
+    for (i, statement) in statements {
+        let failures = ctx.failures;
+        match run_line(ctx, &statement) {
+            Ok(Some(ret)) => println!("{}", ret),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("{}", e.to_string().trim_end());
+                ctx.failures += 1;
+            }
+        }
+        if ctx.failures > failures {
+            failed += 1;
+            let line = statement.lines().next().unwrap_or_default();
+            eprintln!("{}:{}: failed: {}", path.display(), i + 1, line);
+        }
+    }
+    ctx.sourcing.pop();
+    Ok(format!(
+        "Sourced {}: {} commands, {} failed",
+        path.display(),
+        ran,
+        failed
+    ))
+}
+
+// scripts run on the repl side, so their lines go through the callbacks like typed ones
+impl CmdExecutor for SourceOpts {
+    async fn execute<T: Backend>(self, _backend: &mut T) -> anyhow::Result<String> {
+        Err(anyhow!("Scripts can't run in the backend: {}", self.path))
+    }
+}
@@ -0,0 +1,66 @@
+use clap::{ArgMatches, FromArgMatches, Parser, Subcommand};
+
+use crate::{workspace, Backend, CmdExecutor, ReplContext, ReplMsg};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct WorkspaceOpts {
+    #[command(subcommand)]
+    pub action: WorkspaceAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WorkspaceAction {
+    #[command(about = "Create an empty workspace")]
+    Create {
+        #[arg(help = "The name of the workspace")]
+        name: String,
+    },
+    #[command(about = "Switch to a workspace for the rest of the session")]
+    Use {
+        #[arg(help = "The name of the workspace")]
+        name: String,
+    },
+    #[command(about = "List workspaces, the current one marked with *")]
+    List,
+}
+
+pub fn workspace(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let opts = WorkspaceOpts::from_arg_matches(&args).expect("expect workspace options");
+    let switched = match &opts.action {
+        WorkspaceAction::Use { name } => Some(name.clone()),
+        _ => None,
+    };
+    let (msg, rx) = ReplMsg::new(opts);
+    let ret = ctx.send(msg, rx);
+    if let (Some(name), Some(_)) = (switched, &ret) {
+        ctx.switched_workspace(name);
+    }
+    Ok(ret)
+}
+
+impl CmdExecutor for WorkspaceOpts {
+    async fn execute<T: Backend>(self, backend: &mut T) -> anyhow::Result<String> {
+        match self.action {
+            WorkspaceAction::Create { name } => {
+                workspace::create(&name)?;
+                Ok(format!("Created workspace: {}", name))
+            }
+            WorkspaceAction::Use { name } => {
+                backend.use_workspace(&name).await?;
+                Ok(format!("Switched to workspace: {}", name))
+            }
+            WorkspaceAction::List => {
+                let names = workspace::list()?
+                    .into_iter()
+                    .map(|name| match name == backend.workspace() {
+                        true => format!("* {}", name),
+                        false => format!("  {}", name),
+                    })
+                    .collect::<Vec<_>>();
+                Ok(names.join("\n"))
+            }
+        }
+    }
+}
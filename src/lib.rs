@@ -1,15 +1,26 @@
-use std::{ops::Deref, thread};
+use std::{ops::Deref, path::PathBuf, thread, time::Duration};
+
+use anyhow::anyhow;
 
 use backend::DataFusionBackend;
-pub use cli::ReplCommand;
-use cli::{ConnectOpts, DescribeOpts, HeadOpts, ListOpts, SchemaOpts, SqlOpts};
+use clap::CommandFactory;
+pub use cli::{connect_file, source_file, ReplCommand};
+use cli::{
+    ConnectOpts, DescribeOpts, DisconnectOpts, ForgetOpts, HeadOpts, ListOpts, QueryOpts,
+    RefreshOpts, SchemaOpts, SourceOpts, SqlOpts, WorkspaceOpts,
+};
 use crossbeam_channel as mpsc;
 use enum_dispatch::enum_dispatch;
 use reedline_repl_rs::CallBackMap;
+pub use repl::{run_repl, split_statements};
 use tokio::runtime::Runtime;
 
 mod backend;
 mod cli;
+mod repl;
+pub mod workspace;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
 
 #[enum_dispatch]
 trait CmdExecutor {
@@ -18,11 +29,26 @@ trait CmdExecutor {
 
 trait Backend {
     async fn connect(&mut self, opts: &ConnectOpts) -> anyhow::Result<()>;
-    async fn list(&self) -> anyhow::Result<impl ReplDisplay>;
-    async fn schema(&self, name: &str) -> anyhow::Result<impl ReplDisplay>;
-    async fn describe(&self, name: &str) -> anyhow::Result<impl ReplDisplay>;
-    async fn head(&self, name: &str, n: usize) -> anyhow::Result<impl ReplDisplay>;
-    async fn sql(&self, sql: &str) -> anyhow::Result<impl ReplDisplay>;
+    async fn disconnect(&mut self, name: &str) -> anyhow::Result<()>;
+    async fn refresh(&mut self, name: &str) -> anyhow::Result<()>;
+    // reloads the watched datasets whose files changed since they were loaded
+    async fn reload_changed(&mut self) -> Vec<(String, anyhow::Result<()>)>;
+    // removes the dataset from the catalog of datasets connected in every session
+    async fn forget(&mut self, name: &str) -> anyhow::Result<()>;
+    // switches to the datasets and views of another workspace, dropping the connected ones
+    async fn use_workspace(&mut self, name: &str) -> anyhow::Result<()>;
+    fn workspace(&self) -> &str;
+    // queries saved to the catalog of the workspace, run again by name
+    async fn save_query(&mut self, name: &str, sql: &str) -> anyhow::Result<()>;
+    fn query_sql(&self, name: &str) -> anyhow::Result<String>;
+    async fn list_queries(&self) -> anyhow::Result<impl ReplDisplay>;
+    async fn delete_query(&mut self, name: &str) -> anyhow::Result<()>;
+    // datasets saved in the catalog are connected when a command first uses them
+    async fn list(&mut self) -> anyhow::Result<impl ReplDisplay>;
+    async fn schema(&mut self, name: &str) -> anyhow::Result<impl ReplDisplay>;
+    async fn describe(&mut self, name: &str) -> anyhow::Result<impl ReplDisplay>;
+    async fn head(&mut self, name: &str, n: usize) -> anyhow::Result<impl ReplDisplay>;
+    async fn sql(&mut self, sql: &str) -> anyhow::Result<impl ReplDisplay>;
 }
 
 trait ReplDisplay {
@@ -31,11 +57,20 @@ trait ReplDisplay {
 
 pub struct ReplContext {
     pub tx: mpsc::Sender<ReplMsg>,
+    // commands that failed, so scripts can tell which of their lines failed
+    pub failures: usize,
+    // the scripts being sourced, to refuse ones that source themselves
+    sourcing: Vec<PathBuf>,
+    // messages of the backend between commands, printed before the next prompt
+    notices: mpsc::Receiver<String>,
+    // workspaces switched to, so the repl reopens the history of the current one
+    switch_tx: mpsc::Sender<String>,
+    switches: mpsc::Receiver<String>,
 }
 
 pub struct ReplMsg {
     cmd: ReplCommand,
-    tx: oneshot::Sender<String>,
+    tx: oneshot::Sender<anyhow::Result<String>>,
 }
 
 pub type ReplCallBacks = CallBackMap<ReplContext, reedline_repl_rs::Error>;
@@ -43,14 +78,44 @@ pub type ReplCallBacks = CallBackMap<ReplContext, reedline_repl_rs::Error>;
 pub fn get_callbacks() -> ReplCallBacks {
     let mut callbacks = ReplCallBacks::new();
     callbacks.insert("connect".to_string(), cli::connect);
+    callbacks.insert("disconnect".to_string(), cli::disconnect);
+    callbacks.insert("drop".to_string(), cli::disconnect);
+    callbacks.insert("refresh".to_string(), cli::refresh);
+    callbacks.insert("forget".to_string(), cli::forget);
+    callbacks.insert("workspace".to_string(), cli::workspace);
+    callbacks.insert("query".to_string(), cli::query);
     callbacks.insert("list".to_string(), cli::list);
     callbacks.insert("schema".to_string(), cli::schema);
     callbacks.insert("describe".to_string(), cli::describe);
     callbacks.insert("head".to_string(), cli::head);
     callbacks.insert("sql".to_string(), cli::sql);
+    callbacks.insert("source".to_string(), cli::source);
     callbacks
 }
 
+// runs a line as the repl does, through the callback of its command
+pub fn run_line(ctx: &mut ReplContext, line: &str) -> anyhow::Result<Option<String>> {
+    for notice in ctx.notices.try_iter() {
+        eprintln!("{}", notice);
+    }
+    let argv = repl::split_line(line)?;
+    let Some(name) = argv.first() else {
+        return Ok(None);
+    };
+    let callback = get_callbacks()
+        .get(name.as_str())
+        .copied()
+        .ok_or_else(|| anyhow!("Unknown command: {}", name))?;
+    let command = ReplCommand::command()
+        .find_subcommand(name)
+        .cloned()
+        .ok_or_else(|| anyhow!("Unknown command: {}", name))?;
+    let matches = command
+        .try_get_matches_from(&argv)
+        .map_err(|e| anyhow!(e.render().to_string()))?;
+    Ok(callback(matches, ctx)?)
+}
+
 impl Default for ReplContext {
     fn default() -> Self {
         Self::new()
@@ -59,34 +124,87 @@ impl Default for ReplContext {
 
 impl ReplContext {
     pub fn new() -> Self {
+        Self::with_workspace(workspace::DEFAULT_WORKSPACE)
+    }
+
+    pub fn with_workspace(workspace: &str) -> Self {
         let (tx, rx) = mpsc::unbounded::<ReplMsg>();
+        let (notice_tx, notices) = mpsc::unbounded::<String>();
+        let (switch_tx, switches) = mpsc::unbounded::<String>();
         let rt = Runtime::new().expect("Failed to create runtime");
 
-        let mut backend = DataFusionBackend::new();
+        let mut backend = DataFusionBackend::open(workspace);
         thread::Builder::new()
             .name("ReplBackend".to_string())
             .spawn(move || {
-                while let Ok(msg) = rx.recv() {
-                    if let Err(e) = rt.block_on(async {
-                        let ret = msg.cmd.execute(&mut backend).await?;
-                        msg.tx.send(ret)?;
-                        Ok::<_, anyhow::Error>(())
-                    }) {
-                        eprintln!("Failed to process command: {}", e);
+                // watched datasets are checked whenever no command arrived for a while
+                loop {
+                    let msg = match rx.recv_timeout(WATCH_INTERVAL) {
+                        Ok(msg) => msg,
+                        Err(mpsc::RecvTimeoutError::Timeout) => {
+                            // the terminal belongs to the line editor until the next prompt
+                            for (name, result) in rt.block_on(backend.reload_changed()) {
+                                let notice = match result {
+                                    Ok(()) => format!("Reloaded dataset: {}", name),
+                                    Err(e) => format!("Failed to reload dataset {}: {}", name, e),
+                                };
+                                let _ = notice_tx.send(notice);
+                            }
+                            continue;
+                        }
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    };
+                    let ret = rt.block_on(msg.cmd.execute(&mut backend));
+                    if let Err(e) = msg.tx.send(ret) {
+                        eprintln!("Failed to send the result of a command: {}", e);
                     }
                 }
             })
             .unwrap();
-        Self { tx }
+        Self {
+            tx,
+            failures: 0,
+            sourcing: vec![],
+            notices,
+            switch_tx,
+            switches,
+        }
     }
 
-    pub fn send(&self, cmd: ReplMsg, rx: oneshot::Receiver<String>) -> Option<String> {
+    pub fn notices(&self) -> mpsc::Receiver<String> {
+        self.notices.clone()
+    }
+
+    pub fn workspace_switches(&self) -> mpsc::Receiver<String> {
+        self.switches.clone()
+    }
+
+    fn switched_workspace(&self, name: String) {
+        let _ = self.switch_tx.send(name);
+    }
+
+    pub fn send(
+        &mut self,
+        cmd: ReplMsg,
+        rx: oneshot::Receiver<anyhow::Result<String>>,
+    ) -> Option<String> {
         if let Err(e) = self.tx.send(cmd) {
             eprint!("Repl Send Error: {}", e);
             std::process::exit(1);
         }
         // if error return none
-        rx.recv().ok()
+        match rx.recv() {
+            Ok(Ok(ret)) => Some(ret),
+            Ok(Err(e)) => {
+                eprintln!("Failed to process command: {}", e);
+                self.failures += 1;
+                None
+            }
+            Err(_) => {
+                self.failures += 1;
+                None
+            }
+        }
     }
 }
 
@@ -98,7 +216,7 @@ impl Deref for ReplContext {
 }
 
 impl ReplMsg {
-    pub fn new(cmd: impl Into<ReplCommand>) -> (Self, oneshot::Receiver<String>) {
+    pub fn new(cmd: impl Into<ReplCommand>) -> (Self, oneshot::Receiver<anyhow::Result<String>>) {
         let (tx, rx) = oneshot::channel();
         (
             Self {
@@ -1,18 +1,99 @@
-use anyhow::Result;
-use reedline_repl_rs::Repl;
-use taotie::{get_callbacks, ReplCommand, ReplContext};
-const HISTORY_SIZE: usize = 1024;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use taotie::{
+    connect_file, run_line, run_repl, source_file, split_statements, workspace, ReplContext,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "taotie", version, about = "Explore datasets with sql")]
+struct Args {
+    #[arg(
+        short,
+        long,
+        visible_short_alias = 'c',
+        visible_alias = "command",
+        help = "Execute the given commands, separated by ';', and exit instead of starting the repl"
+    )]
+    execute: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        conflicts_with = "execute",
+        help = "Run the commands of a script file and exit instead of starting the repl"
+    )]
+    file: Option<PathBuf>,
+
+    #[arg(
+        short,
+        long,
+        help = "Start in this workspace instead of the default one"
+    )]
+    workspace: Option<String>,
+
+    #[arg(long, help = "Don't run the commands of ~/.taotierc on start")]
+    no_rc: bool,
+
+    #[arg(help = "Datasets to connect on start, each named after its file, e.g. data.parquet")]
+    datasets: Vec<String>,
+}
 
 fn main() -> Result<()> {
-    let ctx = ReplContext::new();
-    let callbacks = get_callbacks();
-    let history_file = dirs::home_dir()
-        .expect("expect home dir")
-        .join(".taotie_history");
-    let mut repl = Repl::new(ctx)
-        .with_history(history_file, HISTORY_SIZE)
-        .with_banner("Welcome to Taotie")
-        .with_derived::<ReplCommand>(callbacks);
-    repl.run()?;
+    let args = Args::parse();
+    let workspace = match args.workspace {
+        Some(name) if !workspace::exists(&name) => {
+            return Err(anyhow!("Workspace not found: {}", name));
+        }
+        Some(name) => name,
+        None => workspace::DEFAULT_WORKSPACE.to_string(),
+    };
+    let mut ctx = ReplContext::with_workspace(&workspace);
+    // a failing line of the startup script is reported, but doesn't stop taotie from starting,
+    // nor count as a failure of the commands given
+    let rc_file = dirs::home_dir().map(|home| home.join(".taotierc"));
+    if let Some(rc_file) = rc_file.filter(|file| !args.no_rc && file.is_file()) {
+        if let Err(e) = source_file(&mut ctx, &rc_file) {
+            eprintln!("Failed to source {}: {}", rc_file.display(), e);
+        }
+        ctx.failures = 0;
+    }
+    for dataset in &args.datasets {
+        if let Some(ret) = connect_file(&mut ctx, dataset) {
+            println!("{}", ret);
+        }
+    }
+
+    match (args.execute, args.file) {
+        (Some(commands), _) => {
+            // the statements as the repl splits them, a command or sql may end without `;`
+            let (mut statements, rest) = split_statements(&commands);
+            statements.extend(rest);
+            for command in statements.into_iter().filter(|s| !s.is_empty()) {
+                match run_line(&mut ctx, command) {
+                    Ok(Some(ret)) => println!("{}", ret),
+                    Ok(None) => {}
+                    Err(e) => {
+                        eprintln!("{}", e.to_string().trim_end());
+                        ctx.failures += 1;
+                    }
+                }
+            }
+        }
+        (None, Some(file)) => {
+            if let Err(e) = source_file(&mut ctx, &file) {
+                return Err(anyhow!("Failed to source {}: {}", file.display(), e));
+            }
+        }
+        (None, None) => {
+            run_repl(ctx, workspace::history_file(&workspace)?)?;
+            return Ok(());
+        }
+    }
+    // scripts and cron jobs need to tell a failed command
+    if ctx.failures > 0 {
+        return Err(anyhow!("{} command(s) failed", ctx.failures));
+    }
     Ok(())
 }
@@ -0,0 +1,317 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use clap::CommandFactory;
+use reedline_repl_rs::{
+    nu_ansi_term::{Color, Style},
+    paint_green_bold,
+    reedline::{
+        ColumnarMenu, DefaultCompleter, DefaultHinter, DefaultPrompt, DefaultPromptSegment, Emacs,
+        ExampleHighlighter, FileBackedHistory, Keybindings, MenuBuilder, Reedline, ReedlineMenu,
+        Signal, ValidationResult, Validator,
+    },
+    Error, Repl,
+};
+
+use crate::{get_callbacks, workspace, ReplCommand, ReplContext};
+
+const HISTORY_SIZE: usize = 1024;
+
+// statements that may be typed without `sql '...'`; `describe` is left out as it is a command
+const SQL_KEYWORDS: [&str; 15] = [
+    "select", "with", "values", "create", "drop", "insert", "update", "delete", "explain", "show",
+    "set", "copy", "prepare", "execute", "truncate",
+];
+
+// a line is sql when it starts with a sql keyword or ends in `;`, unless it starts with a command
+pub fn is_sql(input: &str) -> bool {
+    let input = input.trim();
+    let word = first_word(input);
+    // `drop <name>` disconnects a dataset, `drop table t` or `drop view v;` is sql
+    if word.eq_ignore_ascii_case("drop") {
+        return input.split_whitespace().count() != 2 || ends_statement(input);
+    }
+    if is_command(word) {
+        return false;
+    }
+    SQL_KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+        || ends_statement(input)
+}
+
+fn first_word(input: &str) -> &str {
+    input
+        .trim_start()
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or_default()
+}
+
+fn is_command(word: &str) -> bool {
+    word == "help" || ReplCommand::command().find_subcommand(word).is_some()
+}
+
+// sql goes on until the line ending in `;`
+pub fn is_incomplete(input: &str) -> bool {
+    is_sql(input) && !ends_statement(input)
+}
+
+// whether the last character outside of quotes and comments is a `;`
+fn ends_statement(input: &str) -> bool {
+    let (statements, rest) = split_statements(input);
+    !statements.is_empty() && rest.is_none()
+}
+
+// splits on each `;` outside of quotes and of `--` and `/* */` comments, which sql has but commands
+// don't, as there `--` starts an option; the rest after the last `;` is only kept when it's more
+// than whitespace and comments, or a quote or comment is left open
+pub fn split_statements(input: &str) -> (Vec<&str>, Option<&str>) {
+    let mut statements = vec![];
+    let mut start = 0;
+    let mut quote = None;
+    let mut comment = false;
+    let mut block = false;
+    let mut blank = true;
+    let mut sql = !is_command(first_word(input));
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, c)| *c);
+        if comment {
+            comment = c != '\n';
+            continue;
+        }
+        if block {
+            if c == '*' && next == Some('/') {
+                chars.next();
+                block = false;
+            }
+            continue;
+        }
+        match (quote, c) {
+            (None, '-') if sql && next == Some('-') => comment = true,
+            (None, '/') if sql && next == Some('*') => {
+                chars.next();
+                block = true;
+            }
+            (None, ';') => {
+                statements.push(input[start..i].trim());
+                start = i + 1;
+                blank = true;
+                sql = !is_command(first_word(&input[start..]));
+            }
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                blank = false;
+            }
+            (Some(q), c) if c == q => quote = None,
+            (None, c) if !c.is_whitespace() => blank = false,
+            _ => {}
+        }
+    }
+    let rest = (quote.is_some() || block || !blank).then(|| input[start..].trim());
+    (statements, rest)
+}
+
+// raw sql becomes the query of the sql command, other lines are split like a shell does
+pub fn split_line(line: &str) -> anyhow::Result<Vec<String>> {
+    let line = line.trim();
+    if is_sql(line) {
+        return Ok(vec!["sql".to_string(), line.to_string()]);
+    }
+    shlex::split(line).ok_or_else(|| anyhow!("Invalid command: {}", line))
+}
+
+struct SqlValidator;
+
+impl Validator for SqlValidator {
+    fn validate(&self, line: &str) -> ValidationResult {
+        match is_incomplete(line) {
+            true => ValidationResult::Incomplete,
+            false => ValidationResult::Complete,
+        }
+    }
+}
+
+// `Repl::run` splits every line like a shell and takes no validator, so sql typed as is can't go
+// through it; the repl still runs every line, sql as the query of the sql command
+pub fn run_repl(ctx: ReplContext, history_file: PathBuf) -> anyhow::Result<()> {
+    let notices = ctx.notices();
+    let switches = ctx.workspace_switches();
+    let mut repl = Repl::new(ctx).with_derived::<ReplCommand>(get_callbacks());
+    let mut line_editor = line_editor(&repl).with_history(Box::new(FileBackedHistory::with_file(
+        HISTORY_SIZE,
+        history_file,
+    )?));
+    let prompt = DefaultPrompt::new(
+        DefaultPromptSegment::Basic(paint_green_bold("taotie")),
+        DefaultPromptSegment::Empty,
+    );
+
+    println!("Welcome to Taotie");
+    loop {
+        for notice in notices.try_iter() {
+            eprintln!("{}", notice);
+        }
+        let line = match line_editor.read_line(&prompt)? {
+            Signal::Success(line) => line,
+            Signal::CtrlC => continue,
+            Signal::CtrlD => break,
+        };
+        let result = split_line(&line).and_then(|mut argv| {
+            // the repl finds commands by name only, not by their aliases
+            if let Some(command) = argv
+                .first()
+                .and_then(|name| ReplCommand::command().find_subcommand(name).cloned())
+            {
+                argv[0] = command.get_name().to_string();
+            }
+            Ok(repl.process_argv(argv)?)
+        });
+        if let Err(e) = result {
+            eprintln!("{}", e);
+        }
+        // each workspace keeps its own history
+        if let Some(name) = switches.try_iter().last() {
+            match open_history(&name) {
+                Ok(history) => line_editor = line_editor.with_history(history),
+                Err(e) => eprintln!("Failed to open the history of {}: {}", name, e),
+            }
+        }
+    }
+    Ok(())
+}
+
+// the editor `Repl::run` would build, with the sql validator and sql keywords to complete
+fn line_editor(repl: &Repl<ReplContext, Error>) -> Reedline {
+    let commands = ReplCommand::command()
+        .get_subcommands()
+        .map(|command| command.get_name().to_string())
+        .chain(["help".to_string()])
+        .collect::<Vec<_>>();
+    let mut completer = DefaultCompleter::with_inclusions(&['_']);
+    completer.insert(commands.clone());
+    completer.insert(SQL_KEYWORDS.iter().map(|k| k.to_string()).collect());
+    let mut keybindings = Keybindings::empty();
+    for ((modifier, key_code), event) in repl.get_keybindings() {
+        keybindings.add_binding(modifier, key_code, event);
+    }
+    Reedline::create()
+        .with_edit_mode(Box::new(Emacs::new(keybindings)))
+        .with_completer(Box::new(completer))
+        .with_menu(ReedlineMenu::EngineCompleter(Box::new(
+            ColumnarMenu::default().with_name("completion_menu"),
+        )))
+        .with_highlighter(Box::new(ExampleHighlighter::new(commands)))
+        .with_validator(Box::new(SqlValidator))
+        .with_quick_completions(true)
+        .with_external_printer(repl.external_printer())
+        .with_hinter(Box::new(
+            DefaultHinter::default().with_style(Style::new().italic().fg(Color::LightGray)),
+        ))
+}
+
+fn open_history(workspace: &str) -> anyhow::Result<Box<FileBackedHistory>> {
+    let file = workspace::history_file(workspace)?;
+    Ok(Box::new(FileBackedHistory::with_file(HISTORY_SIZE, file)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tell_sql_from_commands() {
+        assert!(is_sql("select 1"));
+        assert!(is_sql("WITH t AS (SELECT 1) SELECT * FROM t"));
+        assert!(is_sql("values(1)"));
+        assert!(is_sql("from t select a;"));
+        assert!(!is_sql("connect data.csv"));
+        assert!(!is_sql("describe t"));
+        assert!(!is_sql("help connect"));
+        assert!(!is_sql("head t -n 5"));
+        assert!(!is_sql("sql 'select 1;'"));
+    }
+
+    #[test]
+    fn drop_name_disconnects() {
+        assert!(!is_sql("drop t"));
+        assert!(is_sql("drop table t"));
+        assert!(is_sql("drop t;"));
+        assert!(!is_sql("drop 'a;'"));
+    }
+
+    #[test]
+    fn semicolon_outside_quotes_and_comments() {
+        assert!(is_incomplete("select 1"));
+        assert!(!is_incomplete("select 1;"));
+        assert!(!is_incomplete("select 1;  \n"));
+        assert!(is_incomplete("select 'a;b'"));
+        assert!(is_incomplete("select 'a;"));
+        assert!(!is_incomplete("select 'a;b';"));
+        assert!(is_incomplete("select 'it''s;'"));
+        assert!(!is_incomplete("select \"a;\" from t;"));
+        assert!(is_incomplete("select 1 -- done;"));
+        assert!(!is_incomplete("select 1; -- done"));
+        assert!(!is_incomplete("select 1 -- a\n;"));
+        assert!(!is_incomplete("connect data.csv"));
+    }
+
+    #[test]
+    fn semicolon_in_block_comments() {
+        assert!(is_incomplete("select 1 /* done; */"));
+        assert!(!is_incomplete("select 1 /* a; */;"));
+        assert!(!is_incomplete("select 1; /* done */"));
+        assert!(is_incomplete("select 1; /* not done;"));
+        assert!(is_incomplete("select 1 /* a\n; b"));
+        assert!(!is_incomplete("select 1 /* a\n; b */ ;"));
+        assert!(!is_incomplete("select '/*'; "));
+        assert!(!is_incomplete("/* first */ select 1;"));
+        assert_eq!(
+            split_statements("select /* ; */ 1; select 2 /**/;"),
+            (vec!["select /* ; */ 1", "select 2 /**/"], None)
+        );
+        assert_eq!(
+            split_statements("connect data/*.csv -n a; select 1;"),
+            (vec!["connect data/*.csv -n a", "select 1"], None)
+        );
+    }
+
+    #[test]
+    fn split_on_semicolons() {
+        assert_eq!(
+            split_statements("connect a.csv -n a; select 'x;y' from a; "),
+            (vec!["connect a.csv -n a", "select 'x;y' from a"], None)
+        );
+        assert_eq!(
+            split_statements("select 1 -- ; not a command"),
+            (vec![], Some("select 1 -- ; not a command"))
+        );
+        assert_eq!(
+            split_statements("select 1; -- done; really"),
+            (vec!["select 1"], None)
+        );
+        assert_eq!(
+            split_statements("connect a.csv --format csv -n a; select 1"),
+            (vec!["connect a.csv --format csv -n a"], Some("select 1"))
+        );
+        assert_eq!(
+            split_statements("select 1;\nhead t -n 5 --no-header;"),
+            (vec!["select 1", "head t -n 5 --no-header"], None)
+        );
+        assert_eq!(split_statements("select 'a;"), (vec![], Some("select 'a;")));
+    }
+
+    #[test]
+    fn split_sql_and_commands() {
+        assert_eq!(
+            split_line("  select 'a b' from t; ").unwrap(),
+            vec!["sql", "select 'a b' from t;"]
+        );
+        assert_eq!(
+            split_line("connect 'my data.csv' -n t").unwrap(),
+            vec!["connect", "my data.csv", "-n", "t"]
+        );
+        assert!(split_line("connect 'data.csv").is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::anyhow;
+
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+// the default workspace keeps the files used before there were workspaces, the others live in
+// `<config dir>/taotie/workspaces/<name>`
+fn config_dir() -> anyhow::Result<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join("taotie"))
+        .ok_or_else(|| anyhow!("Config dir not found"))
+}
+
+pub fn dir(name: &str) -> anyhow::Result<PathBuf> {
+    match name {
+        DEFAULT_WORKSPACE => config_dir(),
+        name => Ok(config_dir()?.join("workspaces").join(name)),
+    }
+}
+
+pub fn catalog_file(name: &str) -> anyhow::Result<PathBuf> {
+    Ok(dir(name)?.join("catalog.json"))
+}
+
+pub fn history_file(name: &str) -> anyhow::Result<PathBuf> {
+    match name {
+        DEFAULT_WORKSPACE => dirs::home_dir()
+            .map(|home| home.join(".taotie_history"))
+            .ok_or_else(|| anyhow!("Home dir not found")),
+        name => Ok(dir(name)?.join("history")),
+    }
+}
+
+pub fn exists(name: &str) -> bool {
+    name == DEFAULT_WORKSPACE || dir(name).is_ok_and(|dir| dir.is_dir())
+}
+
+pub fn create(name: &str) -> anyhow::Result<()> {
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if name.is_empty() || !valid {
+        return Err(anyhow!(
+            "Invalid workspace name: {}, use letters, digits, _ and -",
+            name
+        ));
+    }
+    if exists(name) {
+        return Err(anyhow!("Workspace already exists: {}", name));
+    }
+    fs::create_dir_all(dir(name)?)?;
+    Ok(())
+}
+
+pub fn list() -> anyhow::Result<Vec<String>> {
+    let mut names = vec![DEFAULT_WORKSPACE.to_string()];
+    let Ok(entries) = fs::read_dir(config_dir()?.join("workspaces")) else {
+        return Ok(names);
+    };
+    let mut others = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect::<Vec<_>>();
+    others.sort();
+    names.extend(others);
+    Ok(names)
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::{env, fs, os::unix::fs::PermissionsExt, path::PathBuf};
+
+use common::{context, run, run_err};
+use taotie::ReplContext;
+
+const JUVENTUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/juventus.csv");
+const REPORT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/report.xlsx");
+
+#[test]
+fn saved_datasets_connect_when_used() {
+    let file = env::temp_dir().join(format!("taotie-saved-{}.csv", std::process::id()));
+    fs::copy(JUVENTUS, &file).unwrap();
+    let mut ctx = context();
+    run(&mut ctx, &format!("connect {} -n sales", file.display()));
+    run(&mut ctx, &format!("connect {} -n players", JUVENTUS));
+    run(&mut ctx, &format!("connect {} -n report", REPORT));
+
+    // connect arguments may hold passwords
+    let catalog = PathBuf::from(env::var("XDG_CONFIG_HOME").unwrap()).join("taotie/catalog.json");
+    let mode = fs::metadata(&catalog).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    // a new session lists the saved datasets without connecting them, even a broken one
+    fs::remove_file(&file).unwrap();
+    let mut ctx = context();
+    let ret = run(&mut ctx, "list");
+    assert!(
+        ret.contains("| sales ") && ret.contains("SAVED TABLE"),
+        "{}",
+        ret
+    );
+    assert!(ret.contains("| report_south "), "{}", ret);
+
+    // a table named like a saved dataset doesn't connect it
+    run(&mut ctx, &format!("connect {} -n sales_local", JUVENTUS));
+    run(&mut ctx, "select count(*) from sales_local");
+    let ret = run(&mut ctx, "select count(*) as n from report_north");
+    assert!(ret.contains("| 2 |"), "{}", ret);
+    let ret = run(&mut ctx, "select count(*) as n from players");
+    assert!(!ret.contains("| 0 |"), "{}", ret);
+    run_err(&mut ctx, "select * from sales");
+}
+
+#[test]
+fn sessions_keep_each_others_changes() {
+    let mut ctx = context();
+    run(&mut ctx, "workspace create shared");
+    let mut first = ReplContext::with_workspace("shared");
+    let mut second = ReplContext::with_workspace("shared");
+
+    // each session saves to the catalog as it is, not as it was when the session started
+    run(
+        &mut first,
+        &format!("connect {} -n first_players", JUVENTUS),
+    );
+    run(
+        &mut second,
+        &format!("connect {} -n second_players", JUVENTUS),
+    );
+    run(
+        &mut first,
+        "create view first_view as select * from first_players",
+    );
+    run(&mut second, "query save second_query 'select 1'");
+    run(&mut first, "query save first_query 'select 2'");
+
+    let mut third = ReplContext::with_workspace("shared");
+    let ret = run(&mut third, "list");
+    for name in ["first_players", "second_players", "first_view"] {
+        assert!(ret.contains(&format!("| {} ", name)), "{}", ret);
+    }
+    let ret = run(&mut third, "query list");
+    assert!(
+        ret.contains("| first_query ") && ret.contains("| second_query "),
+        "{}",
+        ret
+    );
+
+    // the catalog is replaced as a whole, nothing is left aside
+    run(&mut second, "forget first_view");
+    let dir = PathBuf::from(env::var("XDG_CONFIG_HOME").unwrap()).join("taotie/workspaces/shared");
+    let files = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(files, vec!["catalog.json"]);
+    let ret = run(&mut ReplContext::with_workspace("shared"), "list");
+    assert!(!ret.contains("| first_view "), "{}", ret);
+    assert!(ret.contains("| first_players "), "{}", ret);
+}
+
+#[test]
+fn refresh_saved_dataset() {
+    let file = env::temp_dir().join(format!("taotie-refresh-{}.csv", std::process::id()));
+    fs::write(&file, "id\n1\n2\n").unwrap();
+    let mut ctx = context();
+    run(&mut ctx, "workspace create refreshed");
+    run(
+        &mut ReplContext::with_workspace("refreshed"),
+        &format!("connect {} -n refreshed_ids", file.display()),
+    );
+
+    // a saved dataset not used yet in this session is connected rather than not found
+    fs::write(&file, "id\n1\n2\n3\n").unwrap();
+    let mut ctx = ReplContext::with_workspace("refreshed");
+    run(&mut ctx, "refresh refreshed_ids");
+    let ret = run(&mut ctx, "select count(*) as n from refreshed_ids");
+    assert!(ret.contains("| 3 |"), "{}", ret);
+    run_err(&mut ctx, "refresh refreshed_missing");
+
+    let _ = fs::remove_file(&file);
+}
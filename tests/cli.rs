@@ -0,0 +1,86 @@
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use flate2::{write::GzEncoder, Compression};
+
+#[test]
+fn rc_failures_dont_fail_commands() {
+    let home = env::temp_dir().join(format!("taotie-home-{}", std::process::id()));
+    fs::create_dir_all(&home).unwrap();
+    fs::write(
+        home.join(".taotierc"),
+        "connect /nonexistent/data.csv -n gone\n",
+    )
+    .unwrap();
+    let taotie = |command: &str| {
+        Command::new(env!("CARGO_BIN_EXE_taotie"))
+            .env("HOME", &home)
+            .env("XDG_CONFIG_HOME", home.join("config"))
+            .args(["-c", command])
+            .output()
+            .unwrap()
+    };
+
+    let output = taotie("select 1");
+    assert!(output.status.success(), "{:?}", output);
+    let output = taotie("select * from gone");
+    assert!(!output.status.success(), "{:?}", output);
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn gzip_csv_from_stdin() {
+    let home = env::temp_dir().join(format!("taotie-stdin-{}", std::process::id()));
+    fs::create_dir_all(&home).unwrap();
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(b"id,amount\n1,10\n2,20\n3,30\n").unwrap();
+    let data = encoder.finish().unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_taotie"))
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .args([
+            "--no-rc",
+            "-c",
+            "connect - -n piped; select sum(amount) as s from piped",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // the compression and the format are both told from the bytes
+    child.stdin.take().unwrap().write_all(&data).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("| 60 "), "{}", stdout);
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn execute_splits_like_repl() {
+    let home = env::temp_dir().join(format!("taotie-execute-{}", std::process::id()));
+    fs::create_dir_all(&home).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_taotie"))
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .args([
+            "--no-rc",
+            "-c",
+            "select 'a;b' as q; select 1 as one -- ; not a command",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("| a;b |"), "{}", stdout);
+    assert!(stdout.contains("| 1   |"), "{}", stdout);
+
+    let _ = fs::remove_dir_all(&home);
+}
@@ -0,0 +1,268 @@
+use std::{collections::HashMap, fs, fs::File, path::Path, sync::Arc};
+
+use apache_avro::{
+    types::{Record, Value},
+    Schema as AvroSchema, Writer,
+};
+use arrow::{
+    array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray},
+    datatypes::{DataType, Field, Schema, TimeUnit},
+};
+use datafusion::parquet::arrow::{ArrowWriter, PARQUET_FIELD_ID_META_KEY};
+use serde_json::json;
+
+const ENTRY: &str = r#"{"type": "record", "name": "manifest_entry", "fields": [
+  {"name": "status", "type": "int", "field-id": 0},
+  {"name": "snapshot_id", "type": ["null", "long"], "default": null, "field-id": 1},
+  {"name": "data_file", "field-id": 2, "type": {"type": "record", "name": "r2", "fields": [
+    {"name": "content", "type": "int", "field-id": 134},
+    {"name": "file_path", "type": "string", "field-id": 100},
+    {"name": "file_format", "type": "string", "field-id": 101},
+    {"name": "partition", "field-id": 102, "type": {"type": "record", "name": "r102", "fields": [
+      {"name": "ts_day", "type": ["null", {"type": "int", "logicalType": "date"}], "default": null, "field-id": 1000}
+    ]}},
+    {"name": "record_count", "type": "long", "field-id": 103},
+    {"name": "file_size_in_bytes", "type": "long", "field-id": 104},
+    {"name": "null_value_counts", "type": ["null", {"type": "array", "logicalType": "map", "items": {"type": "record", "name": "k121_v122", "fields": [
+      {"name": "key", "type": "int", "field-id": 121}, {"name": "value", "type": "long", "field-id": 122}]}}], "default": null, "field-id": 110},
+    {"name": "lower_bounds", "type": ["null", {"type": "array", "logicalType": "map", "items": {"type": "record", "name": "k126_v127", "fields": [
+      {"name": "key", "type": "int", "field-id": 126}, {"name": "value", "type": "bytes", "field-id": 127}]}}], "default": null, "field-id": 125},
+    {"name": "upper_bounds", "type": ["null", {"type": "array", "logicalType": "map", "items": {"type": "record", "name": "k129_v130", "fields": [
+      {"name": "key", "type": "int", "field-id": 129}, {"name": "value", "type": "bytes", "field-id": 130}]}}], "default": null, "field-id": 128}
+  ]}}
+]}"#;
+
+const MANIFEST_LIST: &str = r#"{"type": "record", "name": "manifest_file", "fields": [
+  {"name": "manifest_path", "type": "string", "field-id": 500},
+  {"name": "manifest_length", "type": "long", "field-id": 501},
+  {"name": "partition_spec_id", "type": "int", "field-id": 502},
+  {"name": "added_snapshot_id", "type": "long", "field-id": 503}
+]}"#;
+
+const DAY_MICROS: i64 = 86_400_000_000;
+// 2024-01-01T00:00:00Z
+const JAN_1: i64 = 1_704_067_200_000_000;
+
+// a row of the table, `amount` is renamed to `total` in the second schema, which adds a new
+// string column named `amount`
+struct Row {
+    id: i64,
+    total: f64,
+    ts: i64,
+    amount: Option<&'static str>,
+}
+
+// a hadoop catalog warehouse with the table `db.events`, partitioned by day(ts): snapshot 1
+// holds ids 1 to 3 in two files written with the first schema, snapshot 2 adds id 4 in a file
+// written after the rename
+pub fn warehouse(root: &Path) {
+    let _ = fs::remove_dir_all(root);
+    let table = root.join("db/events");
+    fs::create_dir_all(table.join("metadata")).unwrap();
+    let uri = |path: &Path| format!("file://{}", path.display());
+
+    let files = [
+        ("00000", false, vec![row(1, 10.0, 1), row(2, 20.0, 2)]),
+        ("00001", false, vec![row(3, 30.0, DAY_MICROS / 1_000_000)]),
+        (
+            "00002",
+            true,
+            vec![Row {
+                amount: Some("x"),
+                ..row(4, 40.0, 2 * DAY_MICROS / 1_000_000)
+            }],
+        ),
+    ];
+    let mut entries = vec![];
+    for (name, renamed, rows) in &files {
+        let path = table.join(format!("data/{}.parquet", name));
+        let size = write_parquet(&path, rows, *renamed);
+        entries.push((uri(&path), size, rows));
+    }
+
+    let m1 = table.join("metadata/m1.avro");
+    let m1_len = write_manifest(&m1, 1, &entries[..2]);
+    let m2 = table.join("metadata/m2.avro");
+    let m2_len = write_manifest(&m2, 2, &entries[2..]);
+    let snap1 = table.join("metadata/snap-1.avro");
+    write_manifest_list(&snap1, &[(uri(&m1), m1_len, 1)]);
+    let snap2 = table.join("metadata/snap-2.avro");
+    write_manifest_list(&snap2, &[(uri(&m1), m1_len, 1), (uri(&m2), m2_len, 2)]);
+
+    let field = |id: i32, name: &str, required: bool, field_type: &str| json!({"id": id, "name": name, "required": required, "type": field_type});
+    let metadata = json!({
+        "format-version": 2,
+        "table-uuid": "6f7e5a8c-2b0e-4d4b-9a57-5d0b8a0f3c11",
+        "location": uri(&table),
+        "last-sequence-number": 2,
+        "last-updated-ms": 1_706_745_600_000_i64,
+        "last-column-id": 4,
+        "current-schema-id": 1,
+        "schemas": [
+            {"type": "struct", "schema-id": 0, "fields": [
+                field(1, "id", true, "long"),
+                field(2, "amount", false, "double"),
+                field(3, "ts", false, "timestamptz"),
+            ]},
+            {"type": "struct", "schema-id": 1, "fields": [
+                field(1, "id", true, "long"),
+                field(2, "total", false, "double"),
+                field(3, "ts", false, "timestamptz"),
+                field(4, "amount", false, "string"),
+            ]},
+        ],
+        "default-spec-id": 0,
+        "partition-specs": [{"spec-id": 0, "fields": [
+            {"name": "ts_day", "transform": "day", "source-id": 3, "field-id": 1000}
+        ]}],
+        "last-partition-id": 1000,
+        "default-sort-order-id": 0,
+        "sort-orders": [{"order-id": 0, "fields": []}],
+        "properties": {},
+        "current-snapshot-id": 2,
+        "snapshots": [
+            {"snapshot-id": 1, "sequence-number": 1, "timestamp-ms": 1_704_110_400_000_i64,
+             "manifest-list": uri(&snap1), "summary": {"operation": "append"}, "schema-id": 0},
+            {"snapshot-id": 2, "sequence-number": 2, "parent-snapshot-id": 1,
+             "timestamp-ms": 1_706_745_600_000_i64, "manifest-list": uri(&snap2),
+             "summary": {"operation": "append"}, "schema-id": 1},
+        ],
+    });
+    fs::write(
+        table.join("metadata/v2.metadata.json"),
+        metadata.to_string(),
+    )
+    .unwrap();
+    fs::write(table.join("metadata/version-hint.text"), "2").unwrap();
+}
+
+fn row(id: i64, total: f64, seconds: i64) -> Row {
+    Row {
+        id,
+        total,
+        ts: JAN_1 + seconds * 1_000_000,
+        amount: None,
+    }
+}
+
+// columns carry their iceberg field ids, named as the schema the file was written with
+fn write_parquet(path: &Path, rows: &[Row], renamed: bool) -> usize {
+    let field = |id: i32, name: &str, data_type: DataType| {
+        Field::new(name, data_type, true).with_metadata(HashMap::from([(
+            PARQUET_FIELD_ID_META_KEY.to_string(),
+            id.to_string(),
+        )]))
+    };
+    let ts_type = DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()));
+    let mut fields = vec![
+        field(1, "id", DataType::Int64),
+        field(
+            2,
+            if renamed { "total" } else { "amount" },
+            DataType::Float64,
+        ),
+        field(3, "ts", ts_type),
+    ];
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.id))),
+        Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.total))),
+        Arc::new(
+            arrow::array::TimestampMicrosecondArray::from_iter_values(rows.iter().map(|r| r.ts))
+                .with_timezone("UTC"),
+        ),
+    ];
+    if renamed {
+        fields.push(field(4, "amount", DataType::Utf8));
+        columns.push(Arc::new(StringArray::from_iter(
+            rows.iter().map(|r| r.amount),
+        )));
+    }
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut writer = ArrowWriter::try_new(File::create(path).unwrap(), schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    fs::metadata(path).unwrap().len() as usize
+}
+
+fn write_manifest(path: &Path, snapshot_id: i64, files: &[(String, usize, &Vec<Row>)]) -> usize {
+    let schema = AvroSchema::parse_str(ENTRY).unwrap();
+    let mut writer = Writer::new(&schema, vec![]);
+    writer
+        .add_user_metadata("partition-spec-id".to_string(), "0")
+        .unwrap();
+    let union = |value: Value| Value::Union(1, Box::new(value));
+    let map = |entries: Vec<(i32, Value)>| {
+        union(Value::Array(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    Value::Record(vec![
+                        ("key".into(), Value::Int(key)),
+                        ("value".into(), value),
+                    ])
+                })
+                .collect(),
+        ))
+    };
+    let bytes = |value: i64| Value::Bytes(value.to_le_bytes().to_vec());
+    for (path, size, rows) in files {
+        let ids = rows.iter().map(|r| r.id);
+        let ts = rows.iter().map(|r| r.ts);
+        let day = (rows[0].ts / DAY_MICROS) as i32;
+        let mut entry = Record::new(&schema).unwrap();
+        entry.put("status", 1);
+        entry.put("snapshot_id", union(Value::Long(snapshot_id)));
+        entry.put(
+            "data_file",
+            Value::Record(vec![
+                ("content".into(), Value::Int(0)),
+                ("file_path".into(), Value::String(path.clone())),
+                ("file_format".into(), Value::String("PARQUET".into())),
+                (
+                    "partition".into(),
+                    Value::Record(vec![("ts_day".into(), union(Value::Date(day)))]),
+                ),
+                ("record_count".into(), Value::Long(rows.len() as i64)),
+                ("file_size_in_bytes".into(), Value::Long(*size as i64)),
+                (
+                    "null_value_counts".into(),
+                    map(vec![(1, Value::Long(0)), (3, Value::Long(0))]),
+                ),
+                (
+                    "lower_bounds".into(),
+                    map(vec![
+                        (1, bytes(ids.clone().min().unwrap())),
+                        (3, bytes(ts.clone().min().unwrap())),
+                    ]),
+                ),
+                (
+                    "upper_bounds".into(),
+                    map(vec![
+                        (1, bytes(ids.max().unwrap())),
+                        (3, bytes(ts.max().unwrap())),
+                    ]),
+                ),
+            ]),
+        );
+        writer.append(entry).unwrap();
+    }
+    let data = writer.into_inner().unwrap();
+    fs::write(path, &data).unwrap();
+    data.len()
+}
+
+fn write_manifest_list(path: &Path, manifests: &[(String, usize, i64)]) {
+    let schema = AvroSchema::parse_str(MANIFEST_LIST).unwrap();
+    let mut writer = Writer::new(&schema, vec![]);
+    for (manifest, len, snapshot_id) in manifests {
+        let mut record = Record::new(&schema).unwrap();
+        record.put("manifest_path", manifest.as_str());
+        record.put("manifest_length", *len as i64);
+        record.put("partition_spec_id", 0);
+        record.put("added_snapshot_id", *snapshot_id);
+        writer.append(record).unwrap();
+    }
+    fs::write(path, writer.into_inner().unwrap()).unwrap();
+}
@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+pub mod iceberg;
+pub mod s3;
+
+use std::{env, sync::Once};
+
+use taotie::{run_line, ReplContext};
+
+static CONFIG: Once = Once::new();
+
+// catalogs and workspaces of the tests go to a config dir of their own
+pub fn context() -> ReplContext {
+    CONFIG.call_once(|| {
+        let dir = env::temp_dir().join(format!("taotie-test-{}", std::process::id()));
+        env::set_var("XDG_CONFIG_HOME", dir);
+    });
+    ReplContext::with_workspace("default")
+}
+
+// runs a line as typed in the repl, panicking when it fails
+pub fn run(ctx: &mut ReplContext, line: &str) -> String {
+    let failures = ctx.failures;
+    let ret = run_line(ctx, line).unwrap_or_else(|e| panic!("{}: {}", line, e));
+    assert_eq!(ctx.failures, failures, "failed: {}", line);
+    ret.unwrap_or_default()
+}
+
+// runs a line expected to fail
+pub fn run_err(ctx: &mut ReplContext, line: &str) {
+    let failures = ctx.failures;
+    if run_line(ctx, line).is_err() {
+        return;
+    }
+    assert_eq!(ctx.failures, failures + 1, "expected to fail: {}", line);
+}
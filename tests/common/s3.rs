@@ -0,0 +1,187 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    thread,
+};
+
+// a minimal stand-in for an s3 compatible server such as minio: path style get, head and
+// ListObjectsV2 over the files of a local directory, where each top level dir is a bucket; the
+// same files can be fetched as plain http urls
+pub struct FakeS3 {
+    pub endpoint: String,
+}
+
+impl FakeS3 {
+    pub fn start(root: &Path) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let root = root.to_path_buf();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let root = root.clone();
+                thread::spawn(move || serve(stream, &root));
+            }
+        });
+        Self { endpoint }
+    }
+}
+
+fn serve(stream: TcpStream, root: &Path) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut stream = stream;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let target = parts.next().unwrap_or_default().to_string();
+        let mut range = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).unwrap_or(0) == 0 {
+                return;
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("range") {
+                    range = Some(value.trim().to_string());
+                }
+            }
+        }
+        let (status, headers, body) = respond(root, &target, range.as_deref());
+        let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\n", status, body.len());
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+        let mut data = response.into_bytes();
+        if method != "HEAD" {
+            data.extend(body);
+        }
+        if stream.write_all(&data).is_err() {
+            return;
+        }
+    }
+}
+
+fn respond(
+    root: &Path,
+    target: &str,
+    range: Option<&str>,
+) -> (&'static str, Vec<(&'static str, String)>, Vec<u8>) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = decode(path.trim_start_matches('/'));
+    let (bucket, key) = path.split_once('/').unwrap_or((&path, ""));
+    let params = query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| (name.to_string(), decode(&value.replace('+', " "))))
+        .collect::<Vec<_>>();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.clone())
+    };
+
+    if key.is_empty() && param("list-type").is_some() {
+        let prefix = param("prefix").unwrap_or_default();
+        let mut keys = vec![];
+        walk(&root.join(bucket), &root.join(bucket), &mut keys);
+        keys.sort();
+        let contents = keys
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, size)| {
+                format!(
+                    "<Contents><Key>{}</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified>\
+                     <ETag>\"{}\"</ETag><Size>{}</Size></Contents>",
+                    key, size, size
+                )
+            })
+            .collect::<String>();
+        let body = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><ListBucketResult><Name>{}</Name>\
+             <Prefix>{}</Prefix><IsTruncated>false</IsTruncated>{}</ListBucketResult>",
+            bucket, prefix, contents
+        );
+        return ("200 OK", vec![], body.into_bytes());
+    }
+
+    let Ok(data) = fs::read(root.join(bucket).join(key)) else {
+        return ("404 Not Found", vec![], vec![]);
+    };
+    let total = data.len();
+    let mut headers = vec![
+        ("ETag", format!("\"{}\"", total)),
+        ("Last-Modified", "Mon, 01 Jan 2024 00:00:00 GMT".to_string()),
+        ("Accept-Ranges", "bytes".to_string()),
+    ];
+    let Some((start, end)) = range.and_then(|range| parse_range(range, total)) else {
+        return ("200 OK", headers, data);
+    };
+    headers.push((
+        "Content-Range",
+        format!("bytes {}-{}/{}", start, end, total),
+    ));
+    ("206 Partial Content", headers, data[start..=end].to_vec())
+}
+
+fn parse_range(range: &str, total: usize) -> Option<(usize, usize)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let (start, end) = match (start, end) {
+        ("", suffix) => (total.checked_sub(suffix.parse().ok()?)?, total - 1),
+        (start, "") => (start.parse().ok()?, total - 1),
+        (start, end) => (
+            start.parse().ok()?,
+            end.parse::<usize>().ok()?.min(total - 1),
+        ),
+    };
+    (start <= end).then_some((start, end))
+}
+
+fn walk(base: &Path, dir: &Path, keys: &mut Vec<(String, u64)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            walk(base, &path, keys);
+        } else if let (Ok(key), Ok(meta)) = (path.strip_prefix(base), entry.metadata()) {
+            keys.push((key.to_string_lossy().into_owned(), meta.len()));
+        }
+    }
+}
+
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok());
+        match (
+            bytes[i],
+            hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+        ) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
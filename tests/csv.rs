@@ -0,0 +1,88 @@
+mod common;
+
+use std::{env, fs};
+
+use common::{context, run, run_err};
+
+#[test]
+fn null_value_in_directory() {
+    let dir = env::temp_dir().join(format!("taotie-csv-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.csv"), "id,amount,name\n1,10,a\n2,NA,NA\n").unwrap();
+    fs::write(dir.join("b.csv"), "id,amount,name\n3,30,NA\n").unwrap();
+
+    let mut ctx = context();
+    // `amount` is a string in one file and a number in the other
+    run(
+        &mut ctx,
+        &format!(
+            "connect {} --format csv --null-value NA -n nv",
+            dir.display()
+        ),
+    );
+    let ret = run(
+        &mut ctx,
+        "select count(amount) as a, count(name) as n from nv",
+    );
+    assert!(ret.contains("| 2 | 1 |"), "{}", ret);
+
+    // the schema's types apply once the marker is gone
+    run(
+        &mut ctx,
+        &format!(
+            "connect {}/*.csv --null-value NA --schema id:int64,amount:int64,name:utf8 -n typed",
+            dir.display()
+        ),
+    );
+    let ret = run(&mut ctx, "select sum(amount) as s from typed");
+    assert!(ret.contains("| 40 |"), "{}", ret);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn dialect_options() {
+    let dir = env::temp_dir().join(format!("taotie-csv-dialect-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let export = dir.join("export.csv");
+    fs::write(
+        &export,
+        "# exported 2024-05-01\n1;'a;b'\n2;'it\\'s'\n# trailer\n3;plain\n",
+    )
+    .unwrap();
+    let codes = dir.join("codes.csv");
+    fs::write(&codes, "id,code\n1,10\n2,20\n3,x\n").unwrap();
+    let mut ctx = context();
+
+    run(
+        &mut ctx,
+        &format!(
+            "connect {} --delimiter ';' --no-header --quote \"'\" --escape '\\' --comment '#' -n export",
+            export.display()
+        ),
+    );
+    let ret = run(&mut ctx, "select count(*) as n from export");
+    assert!(ret.contains("| 3 "), "{}", ret);
+    let ret = run(&mut ctx, "select column_2 from export where column_1 = 1");
+    assert!(ret.contains("| a;b "), "{}", ret);
+    let ret = run(&mut ctx, "select column_2 from export where column_1 = 2");
+    assert!(ret.contains("| it's "), "{}", ret);
+    // without them the same file is a single column of the first line's name
+    run(
+        &mut ctx,
+        &format!("connect {} -n export_raw", export.display()),
+    );
+    run_err(&mut ctx, "select column_2 from export_raw");
+
+    // the rows looked at decide the types, a later string fails a column inferred as numbers
+    run(&mut ctx, &format!("connect {} -n codes", codes.display()));
+    let ret = run(&mut ctx, "select code from codes where id = 3");
+    assert!(ret.contains("| x "), "{}", ret);
+    run(
+        &mut ctx,
+        &format!("connect {} --infer-rows 2 -n codes_few", codes.display()),
+    );
+    run_err(&mut ctx, "select code from codes_few");
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use std::{env, fs, path::Path};
+
+use common::{context, run, run_err};
+
+const SALES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/delta/sales");
+
+// commit 0 adds eu/part-0 and us/part-1, commit 1 adds `ap ac`/part-2 and is checkpointed,
+// commit 2 removes eu/part-0 by an escaped path and adds eu/part-3
+#[test]
+fn delta_checkpoint_and_remove() {
+    let mut ctx = context();
+    run(&mut ctx, &format!("connect {} -n sales", SALES));
+    let ret = run(&mut ctx, "select region, id from sales order by id");
+    assert!(ret.contains("| ap ac  | 4  |"), "{}", ret);
+    assert!(!ret.contains("| 1 "), "{}", ret);
+    let ret = run(&mut ctx, "select sum(amount) as s from sales");
+    assert!(ret.contains("| 120.0 |"), "{}", ret);
+}
+
+#[test]
+fn delta_time_travel() {
+    let mut ctx = context();
+    run(&mut ctx, &format!("connect {} --version 0 -n v0", SALES));
+    let ret = run(&mut ctx, "select sum(amount) as s from v0");
+    assert!(ret.contains("| 60.0 |"), "{}", ret);
+
+    // by the time in commitInfo, the files themselves are as new as the checkout
+    run(
+        &mut ctx,
+        &format!("connect {} --timestamp 2024-02-15 -n feb", SALES),
+    );
+    let ret = run(&mut ctx, "select sum(amount) as s from feb");
+    assert!(ret.contains("| 100.0 |"), "{}", ret);
+    run_err(
+        &mut ctx,
+        &format!("connect {} --timestamp 2023-12-01 -n dec", SALES),
+    );
+}
+
+#[test]
+fn delta_log_features() {
+    let root = env::temp_dir().join(format!("taotie-delta-{}", std::process::id()));
+    copy_dir(Path::new(SALES), &root);
+    let log = root.join("_delta_log");
+
+    // an add path may be an absolute url
+    let extra = root.with_extension("extra");
+    fs::create_dir_all(&extra).unwrap();
+    let file = extra.join("part-4.parquet");
+    fs::copy(root.join("region=us/part-1.parquet"), &file).unwrap();
+    let size = fs::metadata(&file).unwrap().len();
+    fs::write(
+        log.join("00000000000000000003.json"),
+        format!(
+            r#"{{"add":{{"path":"file://{}","partitionValues":{{"region":"us"}},"size":{},"modificationTime":0,"dataChange":true}}}}"#,
+            file.display(),
+            size
+        ),
+    )
+    .unwrap();
+    let mut ctx = context();
+    run(&mut ctx, &format!("connect {} -n absolute", root.display()));
+    let ret = run(&mut ctx, "select count(*) as n from absolute");
+    assert!(ret.contains("| 4 "), "{}", ret);
+
+    // readers must understand every feature the table lists
+    fs::write(
+        log.join("00000000000000000004.json"),
+        r#"{"protocol":{"minReaderVersion":3,"minWriterVersion":7,"readerFeatures":["v2Checkpoint"],"writerFeatures":["v2Checkpoint"]}}"#,
+    )
+    .unwrap();
+    run_err(&mut ctx, &format!("connect {} -n features", root.display()));
+    run(
+        &mut ctx,
+        &format!("connect {} --version 3 -n before", root.display()),
+    );
+
+    // a v2 checkpoint alone, without the commit of its version
+    fs::remove_file(log.join("00000000000000000004.json")).unwrap();
+    fs::write(
+        log.join("00000000000000000004.checkpoint.80a083e8-7026-4e79-81be-64bd76c43a11.json"),
+        "",
+    )
+    .unwrap();
+    run_err(&mut ctx, &format!("connect {} -n v2", root.display()));
+
+    let _ = fs::remove_dir_all(&root);
+    let _ = fs::remove_dir_all(&extra);
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let path = to.join(entry.file_name());
+        match entry.file_type().unwrap().is_dir() {
+            true => copy_dir(&entry.path(), &path),
+            false => {
+                fs::copy(entry.path(), path).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::{env, fs};
+
+use common::{context, run, run_err};
+use taotie::ReplContext;
+
+#[test]
+fn disconnect_workbook_and_replace() {
+    let workbook = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/report.xlsx");
+    let mut ctx = context();
+
+    // every sheet of a workbook goes with it
+    run(&mut ctx, &format!("connect {} -n report", workbook));
+    let ret = run(&mut ctx, "select count(*) as n from report_south");
+    assert!(ret.contains("| 1 |"), "{}", ret);
+    run(&mut ctx, "drop report");
+    run_err(&mut ctx, "select * from report_north");
+    run_err(&mut ctx, "select * from report_south");
+    run_err(&mut ctx, "disconnect report");
+
+    // a failed replace keeps the dataset it was to replace
+    run(&mut ctx, &format!("connect {} -n report", workbook));
+    let bad = env::temp_dir().join(format!("taotie-bad-{}.parquet", std::process::id()));
+    fs::write(&bad, "not parquet").unwrap();
+    run_err(
+        &mut ctx,
+        &format!("connect {} -n report --replace", bad.display()),
+    );
+    let ret = run(&mut ctx, "select sum(amount) as s from report_north");
+    assert!(ret.contains("| 30 "), "{}", ret);
+
+    run(
+        &mut ctx,
+        &format!("connect {} -n report --replace", workbook),
+    );
+    run(&mut ctx, "disconnect report");
+    run_err(&mut ctx, "select * from report_north");
+
+    let _ = fs::remove_file(&bad);
+}
+
+#[test]
+fn disconnect_saved_dataset() {
+    let workbook = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/report.xlsx");
+    let mut ctx = context();
+    run(&mut ctx, "workspace create unused");
+    run(
+        &mut ReplContext::with_workspace("unused"),
+        &format!("connect {} -n unused_report", workbook),
+    );
+
+    // a saved dataset not connected yet can be disconnected, and isn't connected when used later
+    let mut ctx = ReplContext::with_workspace("unused");
+    run(&mut ctx, "disconnect unused_report");
+    run_err(&mut ctx, "select * from unused_report_north");
+    let ret = run(&mut ctx, "list");
+    assert!(!ret.contains("| unused_report"), "{}", ret);
+    run_err(&mut ctx, "disconnect unused_report");
+
+    // it is still saved for later sessions
+    let mut ctx = ReplContext::with_workspace("unused");
+    let ret = run(&mut ctx, "select sum(amount) as s from unused_report_north");
+    assert!(ret.contains("| 30 "), "{}", ret);
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use std::{env, fs};
+
+use common::{context, run, run_err};
+
+const IPC: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/ipc");
+const AVRO: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/sales.avro");
+
+#[test]
+fn sniff_without_extension() {
+    let dir = env::temp_dir().join(format!("taotie-formats-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("sales"), "id,amount\n1,10\n2,20\n").unwrap();
+    let mut ctx = context();
+
+    // the content tells a csv file from a parquet one when there is no extension
+    run(
+        &mut ctx,
+        &format!("connect {} -n sales", dir.join("sales").display()),
+    );
+    let ret = run(&mut ctx, "select sum(amount) as s from sales");
+    assert!(ret.contains("| 30 "), "{}", ret);
+    run(
+        &mut ctx,
+        &format!(
+            "copy (select id, amount * 2 as amount from sales) to '{}' stored as parquet",
+            dir.join("doubled.parquet").display()
+        ),
+    );
+    fs::rename(dir.join("doubled.parquet"), dir.join("doubled")).unwrap();
+    run(
+        &mut ctx,
+        &format!("connect {} -n doubled", dir.join("doubled").display()),
+    );
+    let ret = run(&mut ctx, "select sum(amount) as s from doubled");
+    assert!(ret.contains("| 60 "), "{}", ret);
+
+    // `--format` wins over what the content looks like
+    fs::write(dir.join("lines.csv"), "{\"id\": 1}\n{\"id\": 2}\n").unwrap();
+    run(
+        &mut ctx,
+        &format!(
+            "connect {} --format json -n lines",
+            dir.join("lines.csv").display()
+        ),
+    );
+    let ret = run(&mut ctx, "select sum(id) as s from lines");
+    assert!(ret.contains("| 3 "), "{}", ret);
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect {} --format parquet -n not_parquet",
+            dir.join("sales").display()
+        ),
+    );
+
+    // a missing file only fails once connected
+    run_err(
+        &mut ctx,
+        &format!("connect {} -n missing", dir.join("missing").display()),
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn schema_override() {
+    let dir = env::temp_dir().join(format!("taotie-schema-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let csv = dir.join("events.csv");
+    fs::write(
+        &csv,
+        "id,amount,ts\n1,10,2024-01-01T00:00:00\n2,20,2024-01-02T00:00:00\n",
+    )
+    .unwrap();
+    let json = dir.join("events.json");
+    fs::write(
+        &json,
+        "{\"id\": 1, \"tags\": [\"a\"]}\n{\"id\": 2, \"tags\": []}\n",
+    )
+    .unwrap();
+    let document = dir.join("document.json");
+    fs::write(&document, "[\n  {\"id\": 1, \"score\": 5}\n]\n").unwrap();
+    let mut ctx = context();
+    // the type of a column as the schema command shows it
+    let data_type = |ret: &str, column: &str| {
+        ret.lines()
+            .find(|line| line.starts_with(&format!("| {} ", column)))
+            .and_then(|line| line.split('|').nth(2))
+            .map(|data_type| data_type.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    run(
+        &mut ctx,
+        &format!(
+            "connect {} --schema 'id:int32,amount:float64,ts:timestamp[ms,UTC]' -n csv_typed",
+            csv.display()
+        ),
+    );
+    let ret = run(&mut ctx, "schema csv_typed");
+    assert_eq!(data_type(&ret, "id"), "Int32", "{}", ret);
+    assert_eq!(data_type(&ret, "amount"), "Float64", "{}", ret);
+    assert_eq!(
+        data_type(&ret, "ts"),
+        "Timestamp(Millisecond, Some(\"UTC\"))",
+        "{}",
+        ret
+    );
+    run(
+        &mut ctx,
+        &format!(
+            "connect {} --schema 'id:int16,tags:list<utf8>' -n json_typed",
+            json.display()
+        ),
+    );
+    let ret = run(&mut ctx, "schema json_typed");
+    assert_eq!(data_type(&ret, "id"), "Int16", "{}", ret);
+    assert!(data_type(&ret, "tags").starts_with("List("), "{}", ret);
+    run(
+        &mut ctx,
+        &format!(
+            "connect {} --schema score:float32 -n document_typed",
+            document.display()
+        ),
+    );
+    let ret = run(&mut ctx, "schema document_typed");
+    assert_eq!(data_type(&ret, "score"), "Float32", "{}", ret);
+
+    // a column the file doesn't have is an error rather than a renamed or empty column
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect {} --schema id:int32,nope:float64,ts:utf8 -n csv_nope",
+            csv.display()
+        ),
+    );
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect {} --schema amount:float64,id:int32,ts:utf8 -n csv_order",
+            csv.display()
+        ),
+    );
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect {} --schema id:int64,nope:utf8 -n json_nope",
+            json.display()
+        ),
+    );
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect {} --schema nope:utf8 -n document_nope",
+            document.display()
+        ),
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn arrow_ipc() {
+    let mut ctx = context();
+
+    // the ipc file format, as `.arrow` or feather v2, and the stream format are all read
+    for (file, name) in [
+        ("sales.arrow", "ipc_file"),
+        ("sales.feather", "feather"),
+        ("sales_stream.arrow", "ipc_stream"),
+    ] {
+        run(&mut ctx, &format!("connect {}/{} -n {}", IPC, file, name));
+        let ret = run(
+            &mut ctx,
+            &format!("select sum(amount) as s, count(name) as n from {}", name),
+        );
+        assert!(ret.contains("| 60.0 | 2 "), "{}: {}", name, ret);
+    }
+    run_err(
+        &mut ctx,
+        &format!("connect {}/legacy.feather -n feather_v1", IPC),
+    );
+}
+
+#[test]
+fn avro() {
+    let mut ctx = context();
+
+    run(&mut ctx, &format!("connect {} -n avro_sales", AVRO));
+    let ret = run(
+        &mut ctx,
+        "select sum(amount) as s, count(name) as n from avro_sales",
+    );
+    assert!(ret.contains("| 60.0 | 2 "), "{}", ret);
+    let ret = run(&mut ctx, "select name from avro_sales where id = 2");
+    assert!(ret.contains("| b "), "{}", ret);
+
+    // avro files start with a magic number, so the extension isn't needed
+    let dir = env::temp_dir().join(format!("taotie-avro-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(AVRO, dir.join("sales")).unwrap();
+    run(
+        &mut ctx,
+        &format!("connect {} -n avro_sniffed", dir.join("sales").display()),
+    );
+    let ret = run(&mut ctx, "select count(*) as n from avro_sniffed");
+    assert!(ret.contains("| 3 "), "{}", ret);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use std::{env, fs};
+
+use common::{context, iceberg, run, run_err};
+
+#[test]
+fn iceberg_field_ids_and_pruning() {
+    let root = env::temp_dir().join(format!("taotie-iceberg-{}", std::process::id()));
+    iceberg::warehouse(&root);
+    let mut ctx = context();
+
+    // columns follow their field ids across the rename of amount to total
+    run(
+        &mut ctx,
+        &format!(
+            "connect iceberg://{} --table db.events -n events",
+            root.display()
+        ),
+    );
+    let ret = run(
+        &mut ctx,
+        "select sum(total) as t, count(amount) as a from events",
+    );
+    assert!(ret.contains("| 100.0 | 1 |"), "{}", ret);
+
+    // the first snapshot reads with the schema it was written with
+    run(
+        &mut ctx,
+        &format!(
+            "connect {}/db/events --snapshot-id 1 -n first",
+            root.display()
+        ),
+    );
+    let ret = run(&mut ctx, "select sum(amount) as a from first");
+    assert!(ret.contains("| 60.0 |"), "{}", ret);
+
+    // files are pruned by the bounds of ts, the table is partitioned by day(ts)
+    let sql = "from events where ts >= '2024-01-03T00:00:00Z'";
+    let ret = run(&mut ctx, &format!("explain select id {}", sql));
+    assert!(ret.contains("00002.parquet"), "{}", ret);
+    assert!(!ret.contains("00000.parquet"), "{}", ret);
+    let ret = run(&mut ctx, &format!("select id {}", sql));
+    assert!(ret.contains("| 4  |"), "{}", ret);
+
+    // a warehouse registers its tables under their namespace
+    run(
+        &mut ctx,
+        &format!("connect iceberg://{} -n wh", root.display()),
+    );
+    let ret = run(&mut ctx, r#"select count(*) as n from wh."db.events""#);
+    assert!(ret.contains("| 4 "), "{}", ret);
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn iceberg_files_in_another_store() {
+    let root = env::temp_dir().join(format!("taotie-iceberg-store-{}", std::process::id()));
+    iceberg::warehouse(&root);
+    // the latest snapshot lists its manifests at the same path, but in a bucket
+    let metadata = root.join("db/events/metadata/v2.metadata.json");
+    let snap2 = format!("{}/db/events/metadata/snap-2.avro", root.display());
+    let content = fs::read_to_string(&metadata).unwrap().replace(
+        &format!("file://{}", snap2),
+        &format!("s3://elsewhere{}", snap2),
+    );
+    fs::write(&metadata, content).unwrap();
+    let mut ctx = context();
+
+    run_err(
+        &mut ctx,
+        &format!("connect {}/db/events -n elsewhere", root.display()),
+    );
+    run(
+        &mut ctx,
+        &format!(
+            "connect {}/db/events --snapshot-id 1 -n elsewhere_first",
+            root.display()
+        ),
+    );
+    let ret = run(&mut ctx, "select count(*) as n from elsewhere_first");
+    assert!(ret.contains("| 3 "), "{}", ret);
+
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use std::{env, fs, path::Path};
+
+use common::{context, run, run_err, s3::FakeS3};
+
+// sales partitioned by `year=` then `month=` directories
+fn partitioned(dir: &Path) {
+    let _ = fs::remove_dir_all(dir);
+    for (year, month, rows) in [
+        ("2023", "12", "id,amount\n1,10\n"),
+        ("2024", "01", "id,amount\n2,20\n3,30\n"),
+        ("2024", "02", "id,amount\n4,40\n"),
+    ] {
+        let dir = dir.join(format!("year={}/month={}", year, month));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("sales.csv"), rows).unwrap();
+    }
+}
+
+#[test]
+fn hive_partitions() {
+    let root = env::temp_dir().join(format!("taotie-listing-{}", std::process::id()));
+    let dir = root.join("sales");
+    partitioned(&dir);
+    let mut ctx = context();
+
+    run(&mut ctx, &format!("connect {} -n parts", dir.display()));
+    let ret = run(
+        &mut ctx,
+        "select year, month, sum(amount) as s from parts group by year, month order by year, month",
+    );
+    assert!(ret.contains("| 2023 | 12    | 10 "), "{}", ret);
+    assert!(ret.contains("| 2024 | 01    | 50 "), "{}", ret);
+    assert!(ret.contains("| 2024 | 02    | 40 "), "{}", ret);
+
+    // partition values are strings, filters on them skip whole directories
+    let ret = run(
+        &mut ctx,
+        "select id from parts where year = '2024' and month = '02'",
+    );
+    assert!(ret.contains("| 4 ") && !ret.contains("| 2 "), "{}", ret);
+    let ret = run(
+        &mut ctx,
+        "select count(*) as n from parts where cast(year as int) > 2023",
+    );
+    assert!(ret.contains("| 3 "), "{}", ret);
+
+    // every directory must have the same keys
+    let mixed = root.join("mixed");
+    fs::create_dir_all(mixed.join("year=2024")).unwrap();
+    fs::create_dir_all(mixed.join("region=eu")).unwrap();
+    fs::write(mixed.join("year=2024/a.csv"), "id\n1\n").unwrap();
+    fs::write(mixed.join("region=eu/b.csv"), "id\n2\n").unwrap();
+    run_err(
+        &mut ctx,
+        &format!("connect {} --format csv -n mixed", mixed.display()),
+    );
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn url_globs_and_partitions() {
+    env::set_var("AWS_ACCESS_KEY_ID", "test");
+    env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+    env::set_var("AWS_REGION", "us-east-1");
+    let root = env::temp_dir().join(format!("taotie-listing-s3-{}", std::process::id()));
+    partitioned(&root.join("bucket/sales"));
+    let s3 = FakeS3::start(&root);
+    let mut ctx = context();
+
+    // a prefix is partitioned like a local directory
+    run(
+        &mut ctx,
+        &format!(
+            "connect s3://bucket/sales/ --format csv --endpoint {} -n s3_parts",
+            s3.endpoint
+        ),
+    );
+    let ret = run(
+        &mut ctx,
+        "select sum(amount) as s from s3_parts where year = '2024'",
+    );
+    assert!(ret.contains("| 90 "), "{}", ret);
+
+    // a glob is matched against the files listed, each wildcard within one directory
+    run(
+        &mut ctx,
+        &format!(
+            "connect 's3://bucket/sales/year=2024/month=*/*.csv' --endpoint {} -n s3_2024",
+            s3.endpoint
+        ),
+    );
+    let ret = run(&mut ctx, "select count(*) as n from s3_2024");
+    assert!(ret.contains("| 3 "), "{}", ret);
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect 's3://bucket/sales/*.csv' --endpoint {} -n s3_none",
+            s3.endpoint
+        ),
+    );
+    run(
+        &mut ctx,
+        &format!(
+            "connect 's3://bucket/sales/year=202[3]/*/sales.csv' --endpoint {} -n s3_2023",
+            s3.endpoint
+        ),
+    );
+    let ret = run(&mut ctx, "select id from s3_2023");
+    assert!(ret.contains("| 1 ") && !ret.contains("| 2 "), "{}", ret);
+
+    let _ = fs::remove_dir_all(&root);
+}
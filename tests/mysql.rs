@@ -0,0 +1,171 @@
+mod common;
+
+use std::{
+    env,
+    net::TcpListener,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use common::{context, run};
+use mysql_async::{prelude::Queryable, Pool};
+
+// a throwaway mysqld initialised in a temp dir, or the server at TAOTIE_TEST_MYSQL_URL; the tests
+// are ignored by default and fail when neither is available, so run them with
+// `cargo test -- --ignored`
+struct MySql {
+    url: String,
+    server: Option<(Child, PathBuf)>,
+}
+
+impl MySql {
+    fn start() -> Option<Self> {
+        if let Ok(url) = env::var("TAOTIE_TEST_MYSQL_URL") {
+            return Some(Self { url, server: None });
+        }
+        let dir = env::temp_dir().join(format!("taotie-mysql-{}", std::process::id()));
+        let data = dir.join("data");
+        let port = TcpListener::bind("127.0.0.1:0")
+            .ok()?
+            .local_addr()
+            .ok()?
+            .port();
+        let initialized = Command::new("mysqld")
+            .args(["--no-defaults", "--initialize-insecure", "--user=root"])
+            .arg(format!("--datadir={}", data.display()))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if !initialized.is_ok_and(|status| status.success()) {
+            let _ = std::fs::remove_dir_all(&dir);
+            return None;
+        }
+        let child = Command::new("mysqld")
+            .args(["--no-defaults", "--user=root", "--bind-address=127.0.0.1"])
+            .arg("--mysqlx=OFF")
+            .arg(format!("--port={}", port))
+            .arg(format!("--datadir={}", data.display()))
+            .arg(format!("--socket={}", dir.join("mysqld.sock").display()))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut mysql = Self {
+            url: format!("mysql://root@127.0.0.1:{}/mysql", port),
+            server: Some((child, dir)),
+        };
+        for _ in 0..60 {
+            if mysql.try_execute("CREATE DATABASE taotie").is_ok() {
+                mysql.url = format!("mysql://root@127.0.0.1:{}/taotie", port);
+                return Some(mysql);
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+        None
+    }
+
+    fn try_execute(&self, sql: &str) -> Result<(), mysql_async::Error> {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let pool = Pool::from_url(&self.url)?;
+            let mut conn = pool.get_conn().await?;
+            conn.query_drop(sql).await?;
+            drop(conn);
+            pool.disconnect().await
+        })
+    }
+
+    fn execute(&self, sql: &str) {
+        self.try_execute(sql).unwrap();
+    }
+}
+
+impl Drop for MySql {
+    fn drop(&mut self) {
+        if let Some((child, dir)) = &mut self.server {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs TAOTIE_TEST_MYSQL_URL, or mysqld"]
+fn mysql_table() {
+    let mysql = MySql::start().expect("no mysql server");
+    mysql.execute(
+        "DROP TABLE IF EXISTS taotie_items;
+         CREATE TABLE taotie_items (id INT NOT NULL, name VARCHAR(16), flag TINYINT(1));
+         INSERT INTO taotie_items VALUES (1, 'abc', 1), (2, 'ABC', 2), (3, 'abc ', 0), (4, 'xyz', NULL);
+         DROP TABLE IF EXISTS taotie_many;
+         CREATE TABLE taotie_many (id INT NOT NULL);
+         SET SESSION cte_max_recursion_depth = 20000;
+         INSERT INTO taotie_many
+         WITH RECURSIVE seq(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM seq WHERE i < 20000)
+         SELECT i FROM seq;",
+    );
+    let mut ctx = context();
+    run(
+        &mut ctx,
+        &format!("connect {} -t taotie_items -n my_items", mysql.url),
+    );
+    run(
+        &mut ctx,
+        &format!("connect {} -t taotie_many -n my_many", mysql.url),
+    );
+
+    // string comparisons ignore case and trailing spaces in mysql, so they are left to datafusion
+    let ret = run(
+        &mut ctx,
+        "select id from my_items where name = 'abc' order by id",
+    );
+    assert!(
+        ret.contains("| 1 ") && !ret.contains("| 2 ") && !ret.contains("| 3 "),
+        "{}",
+        ret
+    );
+    let ret = run(
+        &mut ctx,
+        "select count(*) from my_items where name <> 'abc'",
+    );
+    assert!(ret.contains("| 3 "), "{}", ret);
+    let ret = run(
+        &mut ctx,
+        "select count(*) from my_items where name like 'ab%'",
+    );
+    assert!(ret.contains("| 2 "), "{}", ret);
+
+    // tinyint(1) is read as a boolean, any value but 0 being true
+    let ret = run(&mut ctx, "select count(*) from my_items where flag = true");
+    assert!(ret.contains("| 2 "), "{}", ret);
+    let ret = run(
+        &mut ctx,
+        "explain select id from my_items where name = 'abc'",
+    );
+    assert!(ret.contains("FilterExec"), "{}", ret);
+
+    // numeric comparisons are left to mysql
+    let ret = run(&mut ctx, "explain select id from my_items where id = 2");
+    assert!(!ret.contains("FilterExec"), "{}", ret);
+    let ret = run(&mut ctx, "select name from my_items where id = 2");
+    assert!(ret.contains("ABC"), "{}", ret);
+
+    // the query runs when the plan is executed, not when it's planned
+    let ret = run(&mut ctx, "explain select id from my_many");
+    assert!(ret.contains("StreamingTableExec"), "{}", ret);
+
+    // more rows than fit in one batch are streamed
+    let ret = run(&mut ctx, "select count(*) as n, sum(id) as s from my_many");
+    assert!(
+        ret.contains("20000") && ret.contains("200010000"),
+        "{}",
+        ret
+    );
+    let ret = run(&mut ctx, "select id from my_many order by id limit 3");
+    assert!(ret.contains("| 3 ") && !ret.contains("| 4 "), "{}", ret);
+
+    mysql.execute("DROP TABLE taotie_items; DROP TABLE taotie_many");
+}
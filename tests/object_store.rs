@@ -0,0 +1,166 @@
+mod common;
+
+use std::{env, fs};
+
+use common::{context, run, run_err, s3::FakeS3};
+
+// csv files under `bucket/sales`, with a nested dir the glob must not reach into
+fn fixture(name: &str) -> FakeS3 {
+    let root = env::temp_dir().join(format!("taotie-{}-{}", name, std::process::id()));
+    let dir = root.join("bucket/sales");
+    fs::create_dir_all(dir.join("2024-archive")).unwrap();
+    fs::write(dir.join("2024-01.csv"), "id,amount\n1,10\n2,20\n").unwrap();
+    fs::write(dir.join("2024-02.csv"), "id,amount\n3,30\n").unwrap();
+    fs::write(dir.join("2023-12.csv"), "id,amount\n4,40\n").unwrap();
+    fs::write(dir.join("2024-archive/old.csv"), "id,amount\n5,50\n").unwrap();
+    FakeS3::start(&root)
+}
+
+#[test]
+fn s3_glob_and_prefix() {
+    env::set_var("AWS_ACCESS_KEY_ID", "test");
+    env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+    env::set_var("AWS_REGION", "us-east-1");
+    let s3 = fixture("s3");
+    let mut ctx = context();
+
+    // only the files the glob matches, not the whole directory
+    run(
+        &mut ctx,
+        &format!(
+            "connect s3://bucket/sales/2024-*.csv --endpoint {} -n s3_2024",
+            s3.endpoint
+        ),
+    );
+    let ret = run(
+        &mut ctx,
+        "select count(*) as n, sum(amount) as s from s3_2024",
+    );
+    assert!(ret.contains("| 3 ") && ret.contains("| 60 "), "{}", ret);
+
+    run(
+        &mut ctx,
+        &format!(
+            "connect s3://bucket/sales/ --format csv --endpoint {} -n s3_all",
+            s3.endpoint
+        ),
+    );
+    // a prefix is listed like a local directory, without its subdirectories
+    let ret = run(&mut ctx, "select count(*) as n from s3_all");
+    assert!(ret.contains("| 4 "), "{}", ret);
+
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect s3://bucket/sales/1999-*.csv --endpoint {} -n s3_none",
+            s3.endpoint
+        ),
+    );
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect gs://bucket/sales/2024-01.csv --endpoint {} -n gs_sales",
+            s3.endpoint
+        ),
+    );
+
+    // the bucket's store keeps the endpoint it was connected with, for the datasets using it
+    run_err(&mut ctx, "connect s3://bucket/sales/2024-01.csv -n s3_aws");
+    run_err(
+        &mut ctx,
+        "connect s3://bucket/sales/2024-01.csv --endpoint http://127.0.0.1:1 -n s3_other",
+    );
+    let ret = run(&mut ctx, "select count(*) as n from s3_2024");
+    assert!(ret.contains("| 3 "), "{}", ret);
+}
+
+#[test]
+fn http_with_query() {
+    let s3 = fixture("http");
+    let mut ctx = context();
+
+    // the query, e.g. a signed token, isn't part of the extension
+    run(
+        &mut ctx,
+        &format!(
+            "connect '{}/bucket/sales/2024-01.csv?token=x#top' -n http_sales",
+            s3.endpoint
+        ),
+    );
+    let ret = run(&mut ctx, "select sum(amount) as s from http_sales");
+    assert!(ret.contains("| 30 "), "{}", ret);
+
+    // the host's store keeps the query it was connected with, for the datasets using it
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect '{}/bucket/sales/2024-02.csv?token=y' -n http_other",
+            s3.endpoint
+        ),
+    );
+    run_err(
+        &mut ctx,
+        &format!(
+            "connect {}/bucket/sales/2024-02.csv -n http_other",
+            s3.endpoint
+        ),
+    );
+    run(
+        &mut ctx,
+        &format!(
+            "connect '{}/bucket/sales/2024-02.csv?token=x' -n http_other",
+            s3.endpoint
+        ),
+    );
+    let ret = run(&mut ctx, "select sum(amount) as s from http_sales");
+    assert!(ret.contains("| 30 "), "{}", ret);
+}
+
+#[test]
+fn remote_json_document() {
+    env::set_var("AWS_ACCESS_KEY_ID", "test");
+    env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+    env::set_var("AWS_REGION", "us-east-1");
+    let root = env::temp_dir().join(format!("taotie-json-{}", std::process::id()));
+    let dir = root.join("bucket/events");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("events.json"),
+        "[\n  {\"id\": 1, \"kind\": \"a\"},\n  {\"id\": 2, \"kind\": \"b\"}\n]\n",
+    )
+    .unwrap();
+    fs::write(dir.join("lines.json"), "{\"id\": 3}\n{\"id\": 4}\n").unwrap();
+    let s3 = FakeS3::start(&root);
+    let mut ctx = context();
+
+    // a document spread over lines is sniffed from its first bytes, as a local one is
+    run(
+        &mut ctx,
+        &format!(
+            "connect {}/bucket/events/events.json -n http_events",
+            s3.endpoint
+        ),
+    );
+    let ret = run(&mut ctx, "select sum(id) as s from http_events");
+    assert!(ret.contains("| 3 "), "{}", ret);
+    run(
+        &mut ctx,
+        &format!(
+            "connect s3://bucket/events/events.json --endpoint {} -n s3_events",
+            s3.endpoint
+        ),
+    );
+    let ret = run(&mut ctx, "select kind from s3_events where id = 2");
+    assert!(ret.contains("| b "), "{}", ret);
+    run(
+        &mut ctx,
+        &format!(
+            "connect s3://bucket/events/lines.json --endpoint {} -n s3_lines",
+            s3.endpoint
+        ),
+    );
+    let ret = run(&mut ctx, "select sum(id) as s from s3_lines");
+    assert!(ret.contains("| 7 "), "{}", ret);
+
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,232 @@
+mod common;
+
+use std::{
+    env,
+    net::TcpListener,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use common::{context, run, run_err};
+use tokio_postgres::NoTls;
+use url::Url;
+
+// a throwaway server started with initdb and pg_ctl, or the one at TAOTIE_TEST_POSTGRES_URL;
+// the tests are ignored by default and fail when neither is available, e.g. initdb refuses to run
+// as root, so run them with `cargo test -- --ignored`
+struct Postgres {
+    url: String,
+    dir: Option<PathBuf>,
+}
+
+impl Postgres {
+    fn start() -> Option<Self> {
+        if let Ok(url) = env::var("TAOTIE_TEST_POSTGRES_URL") {
+            return Some(Self { url, dir: None });
+        }
+        let dir = env::temp_dir().join(format!("taotie-pg-{}", std::process::id()));
+        let port = TcpListener::bind("127.0.0.1:0")
+            .ok()?
+            .local_addr()
+            .ok()?
+            .port();
+        let initdb = Command::new("initdb")
+            .args(["-U", "postgres", "--auth=trust", "-D"])
+            .arg(&dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if !initdb.is_ok_and(|status| status.success()) {
+            let _ = std::fs::remove_dir_all(&dir);
+            return None;
+        }
+        let pg = Self {
+            url: format!("postgres://postgres@127.0.0.1:{}/postgres", port),
+            dir: Some(dir.clone()),
+        };
+        let started = Command::new("pg_ctl")
+            .args(["-w", "-D"])
+            .arg(&dir)
+            .arg("-o")
+            .arg(format!("-p {} -k {} -h 127.0.0.1", port, dir.display()))
+            .arg("start")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        started.is_ok_and(|status| status.success()).then_some(pg)
+    }
+
+    // a database of its own in utf8, where icu collations are available whatever the server's
+    // default encoding is
+    fn database(&self, name: &str) -> Self {
+        self.execute(&format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", name));
+        self.execute(&format!(
+            "CREATE DATABASE {} ENCODING 'UTF8' LOCALE 'C' TEMPLATE template0",
+            name
+        ));
+        let mut url = Url::parse(&self.url).unwrap();
+        url.set_path(name);
+        Self {
+            url: url.to_string(),
+            dir: None,
+        }
+    }
+
+    fn execute(&self, sql: &str) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let (client, connection) = tokio_postgres::connect(&self.url, NoTls).await.unwrap();
+            tokio::spawn(connection);
+            client.batch_execute(sql).await.unwrap();
+        });
+    }
+}
+
+impl Drop for Postgres {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.dir {
+            let _ = Command::new("pg_ctl")
+                .args(["-m", "immediate", "-D"])
+                .arg(dir)
+                .arg("stop")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs TAOTIE_TEST_POSTGRES_URL, or initdb and pg_ctl"]
+fn postgres_table() {
+    let pg = Postgres::start().expect("no postgres server");
+    pg.execute(
+        "DROP TABLE IF EXISTS taotie_items;
+         CREATE TABLE taotie_items (id int4 NOT NULL, name text, price numeric, day date);
+         INSERT INTO taotie_items
+         SELECT i, 'item' || i, i * 1.5, date '2024-01-01' + i FROM generate_series(1, 20000) i;
+         UPDATE taotie_items SET name = NULL WHERE id = 3;",
+    );
+    let mut ctx = context();
+    run(
+        &mut ctx,
+        &format!("connect {} -t taotie_items -n pg_items", pg.url),
+    );
+
+    // more rows than fit in one batch are streamed
+    let ret = run(&mut ctx, "select count(*) as n, sum(id) as s from pg_items");
+    assert!(
+        ret.contains("20000") && ret.contains("200010000"),
+        "{}",
+        ret
+    );
+
+    let ret = run(
+        &mut ctx,
+        "select id, name, price, day from pg_items where id between 2 and 4 order by id",
+    );
+    assert!(ret.contains("item2") && ret.contains("item4"), "{}", ret);
+    assert!(ret.contains("2024-01-03") && ret.contains("4.5"), "{}", ret);
+    assert_eq!(ret.matches("item").count(), 2, "{}", ret);
+
+    let ret = run(&mut ctx, "select count(*) from pg_items where name is null");
+    assert!(ret.contains("| 1 "), "{}", ret);
+
+    // plain comparisons are left to postgres, like is checked again by datafusion
+    let ret = run(&mut ctx, "explain select id from pg_items where id = 7");
+    assert!(!ret.contains("FilterExec"), "{}", ret);
+    let ret = run(
+        &mut ctx,
+        "explain select id from pg_items where name like 'item1%'",
+    );
+    assert!(ret.contains("FilterExec"), "{}", ret);
+    let ret = run(
+        &mut ctx,
+        "select count(*) from pg_items where name like 'item1%'",
+    );
+    assert!(ret.contains("11111"), "{}", ret);
+
+    let ret = run(&mut ctx, "select id from pg_items order by id limit 3");
+    assert!(ret.contains("| 3 ") && !ret.contains("| 4 "), "{}", ret);
+
+    pg.execute("DROP TABLE taotie_items");
+}
+
+#[test]
+#[ignore = "needs TAOTIE_TEST_POSTGRES_URL, or initdb and pg_ctl"]
+fn postgres_strings() {
+    let pg = Postgres::start().expect("no postgres server");
+    let db = pg.database("taotie_utf8");
+    db.execute(
+        "CREATE TABLE taotie_words (id int4, word text COLLATE \"en-x-icu\", code char(4));
+         INSERT INTO taotie_words VALUES (1, 'B', 'ab'), (2, 'a', 'abcd'), (3, 'b', NULL);",
+    );
+    let mut ctx = context();
+    run(
+        &mut ctx,
+        &format!("connect {} -t taotie_words -n pg_words", db.url),
+    );
+
+    // the collation puts `a` before `B`, datafusion compares bytes as it would for a local table
+    let ret = run(&mut ctx, "select id from pg_words where word < 'a'");
+    assert!(ret.contains("| 1 ") && !ret.contains("| 2 "), "{}", ret);
+    let ret = run(
+        &mut ctx,
+        "select id from pg_words where word between 'B' and 'a' order by id",
+    );
+    assert!(ret.contains("| 1 ") && ret.contains("| 2 "), "{}", ret);
+    assert!(!ret.contains("| 3 "), "{}", ret);
+    let ret = run(&mut ctx, "explain select id from pg_words where word < 'a'");
+    assert!(ret.contains("FilterExec"), "{}", ret);
+
+    // equality doesn't depend on the collation
+    let ret = run(&mut ctx, "explain select id from pg_words where word = 'a'");
+    assert!(!ret.contains("FilterExec"), "{}", ret);
+
+    // char(n) keeps its padding, which postgres ignores when comparing
+    let ret = run(&mut ctx, "select count(*) from pg_words where code = 'ab'");
+    assert!(ret.contains("| 0 "), "{}", ret);
+    let ret = run(
+        &mut ctx,
+        "select count(*) from pg_words where code > 'ab  '",
+    );
+    assert!(ret.contains("| 1 "), "{}", ret);
+
+    run(&mut ctx, "disconnect pg_words");
+    pg.execute("DROP DATABASE taotie_utf8 WITH (FORCE)");
+}
+
+#[test]
+#[ignore = "needs TAOTIE_TEST_POSTGRES_URL, or initdb and pg_ctl"]
+fn postgres_schema() {
+    let pg = Postgres::start().expect("no postgres server");
+    let db = pg.database("taotie_schema");
+    db.execute(
+        "CREATE TABLE orders (id int4, amount float8);
+         CREATE TABLE users (id int4, name text);
+         INSERT INTO orders VALUES (1, 10), (2, 20);
+         INSERT INTO users VALUES (1, 'ann');
+         CREATE VIEW big_orders AS SELECT * FROM orders WHERE amount > 15;",
+    );
+    let mut ctx = context();
+
+    // without a table, every table of the schema is registered under the dataset's name
+    run(&mut ctx, &format!("connect {} -n pg_db", db.url));
+    let ret = run(&mut ctx, "select sum(amount) as total from pg_db.orders");
+    assert!(ret.contains("30.0"), "{}", ret);
+    let ret = run(&mut ctx, "select name from pg_db.users");
+    assert!(ret.contains("ann"), "{}", ret);
+    run_err(&mut ctx, "select * from pg_db.big_orders");
+
+    // views only with --views
+    run(&mut ctx, &format!("connect {} -n pg_views --views", db.url));
+    let ret = run(&mut ctx, "select id from pg_views.big_orders");
+    assert!(ret.contains("| 2 ") && !ret.contains("| 1 "), "{}", ret);
+    let ret = run(&mut ctx, "select count(*) from pg_views.users");
+    assert!(ret.contains("| 1 "), "{}", ret);
+
+    run(&mut ctx, "disconnect pg_db");
+    run(&mut ctx, "disconnect pg_views");
+    pg.execute("DROP DATABASE taotie_schema WITH (FORCE)");
+}
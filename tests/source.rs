@@ -0,0 +1,62 @@
+mod common;
+
+use std::{env, fs};
+
+use common::{context, run, run_err};
+use taotie::run_line;
+
+#[test]
+fn source_script() {
+    let dir = env::temp_dir().join(format!("taotie-source-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let csv = dir.join("sales.csv");
+    fs::write(&csv, "id,amount\n1,10\n2,20\n3,30\n").unwrap();
+    let script = dir.join("script.taotie");
+    fs::write(
+        &script,
+        format!(
+            "# sales of the day\n\
+             connect {} -n source_sales\n\
+             select * from source_missing;\n\
+             \n\
+             create view source_big as\n  select * from source_sales\n  where amount > 10;\n\
+             connect /nonexistent/sales.csv -n source_gone\n\
+             select count(*) as n from source_big;\n",
+            csv.display()
+        ),
+    )
+    .unwrap();
+    let mut ctx = context();
+
+    // a failing line is reported and counted, the lines after it still run
+    let failures = ctx.failures;
+    let ret = run_line(&mut ctx, &format!("source {}", script.display()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        ret,
+        format!("Sourced {}: 5 commands, 2 failed", script.display())
+    );
+    assert_eq!(ctx.failures, failures + 2);
+    let ret = run(&mut ctx, "select count(*) as n from source_big");
+    assert!(ret.contains("| 2 "), "{}", ret);
+
+    // a script sourcing itself would never end
+    let looping = dir.join("loop.taotie");
+    fs::write(&looping, format!("source {}\n", looping.display())).unwrap();
+    let failures = ctx.failures;
+    let ret = run_line(&mut ctx, &format!("source {}", looping.display()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        ret,
+        format!("Sourced {}: 1 commands, 1 failed", looping.display())
+    );
+    assert_eq!(ctx.failures, failures + 1);
+    run_err(
+        &mut ctx,
+        &format!("source {}", dir.join("missing").display()),
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
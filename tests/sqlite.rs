@@ -0,0 +1,54 @@
+mod common;
+
+use std::env;
+
+use common::{context, run, run_err};
+use rusqlite::Connection;
+
+#[test]
+fn sqlite_table() {
+    let path = env::temp_dir().join(format!("taotie-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE items (id INTEGER, name TEXT, price REAL);
+         INSERT INTO items VALUES (1, 'a', 1.5), (2, 'b', 2), (3, 'c', '3.5');
+         CREATE TABLE mixed (id INTEGER, qty INTEGER);
+         INSERT INTO mixed VALUES (1, 10), (2, 'n/a');
+         CREATE TABLE many (id INTEGER);
+         WITH RECURSIVE seq(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM seq WHERE i < 20000)
+         INSERT INTO many SELECT i FROM seq;",
+    )
+    .unwrap();
+    drop(conn);
+
+    let mut ctx = context();
+    run(&mut ctx, &format!("connect {} -n sq", path.display()));
+    let ret = run(&mut ctx, "select sum(price) as total from sq.items");
+    assert!(ret.contains("7.0"), "{}", ret);
+
+    // filters are sent to sqlite and checked again
+    let ret = run(&mut ctx, "explain select id from sq.items where id = 2");
+    assert!(ret.contains("FilterExec"), "{}", ret);
+    let ret = run(&mut ctx, "select name from sq.items where price > 1.5");
+    assert!(ret.contains("| b ") && ret.contains("| c "), "{}", ret);
+    assert!(!ret.contains("| a "), "{}", ret);
+
+    // a value that doesn't fit the declared type fails rather than reading as null, once the
+    // query runs rather than when it's planned
+    run_err(&mut ctx, "select qty from sq.mixed");
+    let ret = run(&mut ctx, "explain select qty from sq.mixed");
+    assert!(ret.contains("StreamingTableExec"), "{}", ret);
+    let ret = run(&mut ctx, "select qty from sq.mixed where id = 1");
+    assert!(ret.contains("10"), "{}", ret);
+
+    // more rows than fit in one batch are streamed
+    let ret = run(&mut ctx, "select count(*) as n, sum(id) as s from sq.many");
+    assert!(
+        ret.contains("20000") && ret.contains("200010000"),
+        "{}",
+        ret
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use std::{env, fs, os::unix::fs::symlink, time::Duration};
+
+use common::{context, run, run_err};
+
+#[test]
+fn watch_reloads_and_stops_at_symlink_cycles() {
+    let dir = env::temp_dir().join(format!("taotie-watch-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("data")).unwrap();
+    fs::write(dir.join("data/a.csv"), "id\n1\n").unwrap();
+    // links back to their own directory, without which the walk would branch on every level
+    symlink(&dir, dir.join("data/loop")).unwrap();
+    symlink(&dir, dir.join("data/again")).unwrap();
+
+    let mut ctx = context();
+    let notices = ctx.notices();
+    run(
+        &mut ctx,
+        &format!("connect {}/data/a.csv --watch -n w", dir.display()),
+    );
+    // the listing refuses the cycle, watching it must not recurse forever first
+    run_err(
+        &mut ctx,
+        &format!("connect {}/data --format csv --watch -n d", dir.display()),
+    );
+
+    std::thread::sleep(Duration::from_millis(1100));
+    fs::write(dir.join("data/a.csv"), "id\n1\n2\n").unwrap();
+    let notice = notices.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(notice.starts_with("Reloaded dataset: "), "{}", notice);
+    let ret = run(&mut ctx, "select count(*) as n from w");
+    assert!(ret.contains("| 2 |"), "{}", ret);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use std::{env, path::PathBuf};
+
+use common::{context, run, run_err};
+use taotie::ReplContext;
+
+const JUVENTUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/juventus.csv");
+
+#[test]
+fn workspace_saved_queries() {
+    let mut ctx = context();
+    run(&mut ctx, "workspace create scouting");
+    run(&mut ctx, "workspace use scouting");
+    run(&mut ctx, &format!("connect {} -n players", JUVENTUS));
+    run(
+        &mut ctx,
+        "query save total 'select count(*) as n from players'",
+    );
+    run_err(&mut ctx, "query save broken 'selec from'");
+    let ret = run(&mut ctx, "query list");
+    assert!(ret.contains("| total "), "{}", ret);
+
+    // a new session of the workspace runs the query, connecting what it reads
+    let mut other = ReplContext::with_workspace("scouting");
+    let expected = run(&mut ctx, "query run total");
+    assert_eq!(run(&mut other, "query run total"), expected);
+
+    // queries belong to their workspace, and switching is left to each session
+    run(&mut ctx, "workspace use default");
+    run_err(&mut ctx, "query run total");
+    let config = PathBuf::from(env::var("XDG_CONFIG_HOME").unwrap()).join("taotie");
+    assert!(!config.join("workspace").exists());
+
+    run(&mut other, "query delete total");
+    run_err(&mut other, "query run total");
+}